@@ -0,0 +1,383 @@
+//! TCP/UDP port forwarding tunneled through an `SshSession`.
+//!
+//! Mirrors the approval channel's event-driven shape (broadcast for status,
+//! a registry keyed by id for control): opening a forward spawns a task that
+//! owns the listener/association and pumps bytes until it's closed or it
+//! errors, and `ForwardEvent`s are broadcast so the frontend can render
+//! forward state without polling.
+
+use russh::client::{self, Handle};
+use russh::ChannelMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream, UdpSocket};
+use tokio::sync::{broadcast, Mutex};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::ssh::Client;
+
+/// Which way traffic flows: `LocalToRemote` binds locally and connects out
+/// through the SSH server; `RemoteToLocal` asks the SSH server to bind and
+/// connects out locally for each accepted connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ForwardDirection {
+    LocalToRemote,
+    RemoteToLocal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ForwardProtocol {
+    #[default]
+    Tcp,
+    Udp,
+}
+
+/// Describes one forward to open.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardSpec {
+    pub direction: ForwardDirection,
+    pub protocol: ForwardProtocol,
+    pub bind_addr: String,
+    pub bind_port: u16,
+    pub target_host: String,
+    pub target_port: u16,
+}
+
+/// Status events broadcast to the frontend over the command WebSocket.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ForwardEvent {
+    Opened { id: String, spec: ForwardSpec },
+    Closed { id: String },
+    Errored { id: String, message: String },
+}
+
+struct ForwardEntry {
+    spec: ForwardSpec,
+    task: JoinHandle<()>,
+    /// Set for `RemoteToLocal` forwards so `close()` can send
+    /// `cancel-tcpip-forward` and release the server's remote listener,
+    /// rather than just tearing down our side of the bookkeeping.
+    handle: Option<Handle<Client>>,
+}
+
+/// Tracks forwards active on one `SshSession` and broadcasts their status.
+pub struct ForwardRegistry {
+    forwards: Mutex<HashMap<Uuid, ForwardEntry>>,
+    event_tx: broadcast::Sender<ForwardEvent>,
+    /// Remote-listen-port -> local target, consulted by `Client`'s
+    /// forwarded-tcpip handler to route an incoming remote connection to the
+    /// right local target (populated by `RemoteToLocal` forwards).
+    remote_targets: Arc<Mutex<HashMap<u32, (String, u16)>>>,
+}
+
+impl ForwardRegistry {
+    pub fn new() -> Self {
+        let (event_tx, _) = broadcast::channel(64);
+        Self {
+            forwards: Mutex::new(HashMap::new()),
+            event_tx,
+            remote_targets: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Subscribe to forward status events.
+    pub fn subscribe(&self) -> broadcast::Receiver<ForwardEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// The shared table `Client::channel_open_forwarded_tcpip` consults to
+    /// route an incoming forwarded channel to its local target.
+    pub fn remote_targets(&self) -> Arc<Mutex<HashMap<u32, (String, u16)>>> {
+        self.remote_targets.clone()
+    }
+
+    /// Open `spec`, spawning the task that owns it, and return its id.
+    pub async fn open(&self, handle: Handle<Client>, spec: ForwardSpec) -> anyhow::Result<Uuid> {
+        let id = Uuid::new_v4();
+        let event_tx = self.event_tx.clone();
+
+        // Kept alongside the task for `RemoteToLocal` forwards so `close()`
+        // can send `cancel-tcpip-forward` for the exact handle that opened
+        // the remote listener.
+        let remote_handle = if spec.direction == ForwardDirection::RemoteToLocal {
+            Some(handle.clone())
+        } else {
+            None
+        };
+
+        let task = match (spec.direction, spec.protocol) {
+            (ForwardDirection::LocalToRemote, ForwardProtocol::Tcp) => {
+                spawn_local_to_remote_tcp(id, spec.clone(), handle, event_tx.clone()).await?
+            }
+            (ForwardDirection::RemoteToLocal, ForwardProtocol::Tcp) => {
+                spawn_remote_to_local_tcp(
+                    id,
+                    spec.clone(),
+                    handle,
+                    self.remote_targets(),
+                    event_tx.clone(),
+                )
+                .await?
+            }
+            (ForwardDirection::LocalToRemote, ForwardProtocol::Udp) => {
+                spawn_local_to_remote_udp(id, spec.clone(), handle, event_tx.clone()).await?
+            }
+            (ForwardDirection::RemoteToLocal, ForwardProtocol::Udp) => {
+                anyhow::bail!("remote-to-local UDP forwarding is not supported")
+            }
+        };
+
+        self.forwards.lock().await.insert(
+            id,
+            ForwardEntry {
+                spec: spec.clone(),
+                task,
+                handle: remote_handle,
+            },
+        );
+
+        let _ = event_tx.send(ForwardEvent::Opened {
+            id: id.to_string(),
+            spec,
+        });
+
+        Ok(id)
+    }
+
+    /// Close a forward by id, tearing down its task. Returns false if `id`
+    /// wasn't an active forward.
+    pub async fn close(&self, id: Uuid) -> bool {
+        let entry = self.forwards.lock().await.remove(&id);
+        match entry {
+            Some(entry) => {
+                if entry.spec.direction == ForwardDirection::RemoteToLocal {
+                    if let Some(handle) = &entry.handle {
+                        // Tell the SSH server to release its remote listener;
+                        // best-effort since the connection may already be gone.
+                        let _ = handle
+                            .cancel_tcpip_forward(
+                                entry.spec.bind_addr.clone(),
+                                entry.spec.bind_port as u32,
+                            )
+                            .await;
+                    }
+                    let port = entry.spec.bind_port as u32;
+                    self.remote_targets.lock().await.remove(&port);
+                }
+                entry.task.abort();
+                let _ = self.event_tx.send(ForwardEvent::Closed { id: id.to_string() });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Currently active forwards, for listing in the UI.
+    pub async fn list(&self) -> Vec<(Uuid, ForwardSpec)> {
+        self.forwards
+            .lock()
+            .await
+            .iter()
+            .map(|(id, entry)| (*id, entry.spec.clone()))
+            .collect()
+    }
+}
+
+impl Default for ForwardRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn spawn_local_to_remote_tcp(
+    id: Uuid,
+    spec: ForwardSpec,
+    handle: Handle<Client>,
+    event_tx: broadcast::Sender<ForwardEvent>,
+) -> anyhow::Result<JoinHandle<()>> {
+    let listener = TcpListener::bind((spec.bind_addr.as_str(), spec.bind_port))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind local forward listener: {}", e))?;
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let (stream, peer_addr) = match listener.accept().await {
+                Ok(pair) => pair,
+                Err(e) => {
+                    let _ = event_tx.send(ForwardEvent::Errored {
+                        id: id.to_string(),
+                        message: format!("Accept failed: {}", e),
+                    });
+                    break;
+                }
+            };
+
+            let channel = match handle
+                .channel_open_direct_tcpip(
+                    spec.target_host.clone(),
+                    spec.target_port as u32,
+                    peer_addr.ip().to_string(),
+                    peer_addr.port() as u32,
+                )
+                .await
+            {
+                Ok(channel) => channel,
+                Err(e) => {
+                    let _ = event_tx.send(ForwardEvent::Errored {
+                        id: id.to_string(),
+                        message: format!("direct-tcpip open failed: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            tokio::spawn(pump_tcp_channel(stream, channel));
+        }
+    }))
+}
+
+async fn spawn_remote_to_local_tcp(
+    id: Uuid,
+    spec: ForwardSpec,
+    handle: Handle<Client>,
+    remote_targets: Arc<Mutex<HashMap<u32, (String, u16)>>>,
+    event_tx: broadcast::Sender<ForwardEvent>,
+) -> anyhow::Result<JoinHandle<()>> {
+    handle
+        .tcpip_forward(spec.bind_addr.clone(), spec.bind_port as u32)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to request remote forward: {}", e))?;
+
+    remote_targets.lock().await.insert(
+        spec.bind_port as u32,
+        (spec.target_host.clone(), spec.target_port),
+    );
+
+    // Incoming forwarded channels are delivered to `Client`'s
+    // `channel_open_forwarded_tcpip` handler (looked up via `remote_targets`)
+    // rather than through this task, since russh hands them to the client
+    // `Handler`, not back through `Handle`. This task just holds the
+    // registration open until the forward is closed.
+    Ok(tokio::spawn(async move {
+        let _keepalive = (handle, remote_targets);
+        let _ = event_tx; // status is emitted by `ForwardRegistry::open`/`close`
+        std::future::pending::<()>().await;
+    }))
+}
+
+/// UDP has no native SSH channel type, so each distinct source address gets
+/// its own `direct-tcpip` channel, lazily opened on first datagram and
+/// reused for every later datagram from that source (an approximation of a
+/// UDP "connection", not a protocol-level UDP forward). Associations are
+/// never expired; they last until the forward itself is closed.
+async fn spawn_local_to_remote_udp(
+    id: Uuid,
+    spec: ForwardSpec,
+    handle: Handle<Client>,
+    event_tx: broadcast::Sender<ForwardEvent>,
+) -> anyhow::Result<JoinHandle<()>> {
+    let socket = UdpSocket::bind((spec.bind_addr.as_str(), spec.bind_port))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to bind local UDP forward socket: {}", e))?;
+    let socket = Arc::new(socket);
+
+    Ok(tokio::spawn(async move {
+        let associations: Arc<Mutex<HashMap<SocketAddr, Arc<Mutex<russh::Channel<client::Msg>>>>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        let mut buf = vec![0u8; 65536];
+
+        loop {
+            let (n, source) = match socket.recv_from(&mut buf).await {
+                Ok(pair) => pair,
+                Err(e) => {
+                    let _ = event_tx.send(ForwardEvent::Errored {
+                        id: id.to_string(),
+                        message: format!("UDP recv failed: {}", e),
+                    });
+                    break;
+                }
+            };
+
+            let channel = {
+                let mut associations = associations.lock().await;
+                if let Some(channel) = associations.get(&source) {
+                    channel.clone()
+                } else {
+                    let opened = handle
+                        .channel_open_direct_tcpip(
+                            spec.target_host.clone(),
+                            spec.target_port as u32,
+                            source.ip().to_string(),
+                            source.port() as u32,
+                        )
+                        .await;
+                    match opened {
+                        Ok(channel) => {
+                            let channel = Arc::new(Mutex::new(channel));
+                            associations.insert(source, channel.clone());
+                            channel
+                        }
+                        Err(e) => {
+                            let _ = event_tx.send(ForwardEvent::Errored {
+                                id: id.to_string(),
+                                message: format!("direct-tcpip open failed: {}", e),
+                            });
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let mut channel = channel.lock().await;
+            if channel
+                .data(std::io::Cursor::new(buf[..n].to_vec()))
+                .await
+                .is_err()
+            {
+                continue;
+            }
+
+            if let Some(ChannelMsg::Data { data }) = channel.wait().await {
+                let _ = socket.send_to(&data, source).await;
+            }
+        }
+    }))
+}
+
+/// Pump bytes between `stream` and `channel` until either side closes.
+async fn pump_tcp_channel(mut stream: TcpStream, mut channel: russh::Channel<client::Msg>) {
+    let mut buf = vec![0u8; 8192];
+    loop {
+        tokio::select! {
+            result = stream.read(&mut buf) => {
+                match result {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if channel.data(std::io::Cursor::new(buf[..n].to_vec())).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            msg = channel.wait() => {
+                match msg {
+                    Some(ChannelMsg::Data { data }) => {
+                        if stream.write_all(&data).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(ChannelMsg::Eof) | None => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
@@ -1,59 +1,161 @@
 use anyhow::{Context, Result};
 use portable_pty::{CommandBuilder, PtySize};
+use std::collections::{HashMap, VecDeque};
 use std::io::{Read, Write};
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex, RwLock};
 
-/// Manages an interactive Gemini CLI terminal session using PTY
+/// Describes an interactive agent CLI that can be driven through the PTY + MCP plumbing.
+///
+/// This is deliberately not limited to Gemini: any interactive coding-agent CLI
+/// (Claude, Ollama-backed tools, etc.) can be run by pointing `command` at its
+/// binary and telling us which environment variable it expects its API key in.
+#[derive(Debug, Clone)]
+pub struct AgentBackend {
+    /// Human-readable name, used only for logging.
+    pub name: String,
+    /// Path or name of the CLI binary to spawn.
+    pub command: String,
+    /// Extra arguments passed to the binary on every spawn.
+    pub args: Vec<String>,
+    /// Name of the environment variable the backend reads its API key from.
+    pub auth_token_env_var_name: String,
+    /// Additional static environment variables to set on every spawn.
+    pub env: HashMap<String, String>,
+}
+
+impl AgentBackend {
+    /// The default backend: the `gemini` CLI, authenticated via `GEMINI_API_KEY`.
+    pub fn gemini() -> Self {
+        Self {
+            name: "gemini".to_string(),
+            command: "gemini".to_string(),
+            args: Vec::new(),
+            auth_token_env_var_name: "GEMINI_API_KEY".to_string(),
+            env: HashMap::new(),
+        }
+    }
+}
+
+impl Default for AgentBackend {
+    fn default() -> Self {
+        Self::gemini()
+    }
+}
+
+/// Environment variables the browser terminal is allowed to propagate into
+/// the spawned process. Deliberately short: this data comes from the
+/// client, so anything that could redirect auth, paths, or shell behavior
+/// (`PATH`, `HOME`, `SHELL`, ...) stays out and is only ever set from the
+/// server's own environment instead.
+const SAFE_CLIENT_ENV_VARS: &[&str] = &["LANG", "LC_ALL", "LC_CTYPE", "COLORTERM"];
+
+/// Terminal type, size, and client-supplied environment to apply to a newly
+/// spawned PTY process, so programs inside it render exactly as they would
+/// in the browser's own terminal.
+#[derive(Debug, Clone)]
+pub struct TerminalInit {
+    pub term: String,
+    pub cols: u16,
+    pub rows: u16,
+    /// Client-supplied environment variables, filtered against
+    /// `SAFE_CLIENT_ENV_VARS` before being applied.
+    pub env: HashMap<String, String>,
+}
+
+impl Default for TerminalInit {
+    fn default() -> Self {
+        Self {
+            term: "xterm-256color".to_string(),
+            cols: 80,
+            rows: 24,
+            env: HashMap::new(),
+        }
+    }
+}
+
+/// Manages an interactive agent CLI terminal session using PTY
 pub struct GeminiTerminal {
     pty_pair: Arc<Mutex<portable_pty::PtyPair>>,
     child: Arc<Mutex<Box<dyn portable_pty::Child + Send>>>,
 }
 
 impl GeminiTerminal {
-    /// Spawn a new interactive Gemini CLI process with PTY
+    /// Spawn a new interactive agent CLI process with PTY
     ///
     /// # Arguments
+    /// * `backend` - Which agent CLI to run and how to authenticate it
     /// * `session_api_key` - Optional per-session API key from web authentication
-    pub fn spawn(session_api_key: Option<String>) -> Result<Self> {
+    /// * `init` - Terminal type/size/env negotiated with the browser terminal
+    pub fn spawn(
+        backend: &AgentBackend,
+        session_api_key: Option<String>,
+        init: &TerminalInit,
+    ) -> Result<Self> {
         let pty_system = portable_pty::native_pty_system();
 
-        // Create a PTY with initial size
+        // Create a PTY with the size the browser terminal reported
         let pty_pair = pty_system
             .openpty(PtySize {
-                rows: 24,
-                cols: 80,
+                rows: init.rows,
+                cols: init.cols,
                 pixel_width: 0,
                 pixel_height: 0,
             })
             .context("Failed to create PTY")?;
 
-        // Build command to run gemini CLI
-        let mut cmd = CommandBuilder::new("gemini");
+        // Build command to run the configured backend CLI
+        let mut cmd = CommandBuilder::new(&backend.command);
+        for arg in &backend.args {
+            cmd.arg(arg);
+        }
 
-        // Set terminal type for proper PTY operation
-        if let Ok(term) = std::env::var("TERM") {
-            cmd.env("TERM", term);
-        } else {
-            cmd.env("TERM", "xterm-256color");
+        // Set terminal type for proper PTY operation, as negotiated with the
+        // browser terminal (defaults to xterm-256color if it didn't send one).
+        cmd.env("TERM", &init.term);
+
+        // Client-supplied environment, restricted to SAFE_CLIENT_ENV_VARS.
+        for key in SAFE_CLIENT_ENV_VARS {
+            if let Some(value) = init.env.get(*key) {
+                cmd.env(key, value);
+            }
+        }
+
+        // Static environment overrides declared by the backend
+        for (key, value) in &backend.env {
+            cmd.env(key, value);
         }
 
         // Priority for API key: session key > environment variable
-        // This allows per-session authentication from the web UI
+        // This allows per-session authentication from the web UI, and writes
+        // it into whichever env var this backend expects (not always GEMINI_API_KEY).
+        let env_var = backend.auth_token_env_var_name.as_str();
         if let Some(ref key) = session_api_key {
             if !key.is_empty() {
-                cmd.env("GEMINI_API_KEY", key);
-                tracing::info!("Gemini CLI starting with per-session API key authentication");
+                cmd.env(env_var, key);
+                tracing::info!(
+                    "{} starting with per-session API key authentication",
+                    backend.name
+                );
             }
-        } else if let Ok(api_key) = std::env::var("GEMINI_API_KEY") {
+        } else if let Ok(api_key) = std::env::var(env_var) {
             if !api_key.is_empty() {
-                cmd.env("GEMINI_API_KEY", api_key);
-                tracing::info!("Gemini CLI starting with environment API key authentication");
+                cmd.env(env_var, api_key);
+                tracing::info!(
+                    "{} starting with environment API key authentication",
+                    backend.name
+                );
             } else {
-                tracing::info!("No API key provided - Gemini CLI will show interactive authentication");
+                tracing::info!(
+                    "No API key provided - {} will show interactive authentication",
+                    backend.name
+                );
             }
         } else {
-            tracing::info!("No API key provided - Gemini CLI will show interactive authentication");
+            tracing::info!(
+                "No API key provided - {} will show interactive authentication",
+                backend.name
+            );
         }
 
         // Pass through HOME for OAuth credential storage
@@ -62,23 +164,25 @@ impl GeminiTerminal {
             tracing::debug!("HOME directory set to: {}", home);
         }
 
-        // Pass XDG config directory for Gemini CLI credentials
+        // Pass XDG config directory for agent CLI credentials
         if let Ok(xdg_config) = std::env::var("XDG_CONFIG_HOME") {
             cmd.env("XDG_CONFIG_HOME", xdg_config);
         }
 
-        // Pass PATH to ensure gemini CLI can find node and other dependencies
+        // Pass PATH to ensure the agent CLI can find node and other dependencies
         if let Ok(path) = std::env::var("PATH") {
             cmd.env("PATH", path);
         }
 
         // Spawn the process in the PTY
-        let child = pty_pair
-            .slave
-            .spawn_command(cmd)
-            .context("Failed to spawn gemini CLI. Is it installed?")?;
+        let child = pty_pair.slave.spawn_command(cmd).with_context(|| {
+            format!(
+                "Failed to spawn {} CLI. Is it installed?",
+                backend.command
+            )
+        })?;
 
-        tracing::info!("Gemini CLI process spawned successfully");
+        tracing::info!("{} process spawned successfully", backend.name);
 
         Ok(Self {
             pty_pair: Arc::new(Mutex::new(pty_pair)),
@@ -139,3 +243,126 @@ impl GeminiTerminal {
         Ok(())
     }
 }
+
+/// How much recent PTY output each `GeminiTerminalSession` keeps for
+/// scrollback replay, capped by total bytes rather than chunk count since
+/// chunk size varies a lot (single keystrokes vs. a burst of output).
+const SCROLLBACK_CAP_BYTES: usize = 64 * 1024;
+
+/// A `GeminiTerminal` shared across every WebSocket connected to one
+/// session, so a dropped connection (or a second viewer) doesn't lose the
+/// running agent.
+///
+/// The PTY reader is owned by a single background task that fans output out
+/// to a bounded scrollback buffer (for replay on (re)connect) and a
+/// broadcast channel (for whoever's currently watching); the writer is
+/// shared behind a mutex so input from any connected client is serialized
+/// onto the one PTY.
+pub struct GeminiTerminalSession {
+    terminal: GeminiTerminal,
+    scrollback: Arc<RwLock<VecDeque<String>>>,
+    output_tx: broadcast::Sender<String>,
+    writer: Arc<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl GeminiTerminalSession {
+    /// Spawn the agent CLI and start the output fan-out task.
+    pub async fn spawn(
+        backend: &AgentBackend,
+        session_api_key: Option<String>,
+        init: &TerminalInit,
+    ) -> Result<Self> {
+        let terminal = GeminiTerminal::spawn(backend, session_api_key, init)?;
+
+        let scrollback = Arc::new(RwLock::new(VecDeque::new()));
+        let (output_tx, _) = broadcast::channel(256);
+
+        let reader = terminal.get_reader().await;
+        let writer = Arc::new(Mutex::new(terminal.take_writer().await));
+
+        let scrollback_for_task = scrollback.clone();
+        let output_tx_for_task = output_tx.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut reader = reader;
+            let mut buf = vec![0u8; 4096];
+            let rt = tokio::runtime::Handle::current();
+
+            tracing::info!("Gemini PTY output fan-out task started");
+
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => {
+                        tracing::warn!("Gemini PTY reached EOF - process may have exited");
+                        break;
+                    }
+                    Ok(n) => {
+                        let chunk = String::from_utf8_lossy(&buf[..n]).to_string();
+
+                        rt.block_on(async {
+                            let mut buffer = scrollback_for_task.write().await;
+                            buffer.push_back(chunk.clone());
+                            let mut total: usize = buffer.iter().map(|s| s.len()).sum();
+                            while total > SCROLLBACK_CAP_BYTES {
+                                let Some(oldest) = buffer.pop_front() else {
+                                    break;
+                                };
+                                total -= oldest.len();
+                            }
+                        });
+
+                        // No receivers is not an error - nobody's watching right now.
+                        let _ = output_tx_for_task.send(chunk);
+                    }
+                    Err(e) => {
+                        tracing::error!("Error reading from Gemini PTY: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            tracing::info!("Gemini PTY output fan-out task ended");
+        });
+
+        Ok(Self {
+            terminal,
+            scrollback,
+            output_tx,
+            writer,
+        })
+    }
+
+    /// Buffered scrollback, oldest first, for replay to a newly connected client.
+    ///
+    /// Call `subscribe()` *before* this, not after - the fan-out task started
+    /// in `spawn` pushes into the scrollback buffer and broadcasts in the same step, so
+    /// fetching scrollback first can miss a chunk emitted between the
+    /// snapshot and the later subscribe: it lands in neither. Subscribing
+    /// first means any overlap is just a harmless duplicate instead.
+    pub async fn scrollback(&self) -> Vec<String> {
+        self.scrollback.read().await.iter().cloned().collect()
+    }
+
+    /// Subscribe to live output chunks. Call this before `scrollback()` so a
+    /// chunk emitted in between isn't lost - see `scrollback()` for why.
+    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+        self.output_tx.subscribe()
+    }
+
+    /// Write input from any connected client onto the single shared PTY writer.
+    pub async fn write_input(&self, data: &str) -> Result<()> {
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(data.as_bytes())
+            .context("Error writing to Gemini PTY")?;
+        writer.flush().context("Error flushing Gemini PTY")?;
+        Ok(())
+    }
+
+    pub async fn resize(&self, cols: u16, rows: u16) -> Result<()> {
+        self.terminal.resize(cols, rows).await
+    }
+
+    pub async fn is_running(&self) -> bool {
+        self.terminal.is_running().await
+    }
+}
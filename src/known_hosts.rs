@@ -0,0 +1,267 @@
+//! Trust-on-first-use SSH host key verification, persisted to a
+//! `known_hosts`-style file.
+//!
+//! `SshSession::connect` consults a `KnownHostsStore` before the handshake
+//! completes: an unseen host is recorded (if `HostKeyPolicy` allows it), and
+//! a host whose key no longer matches what's on record is rejected with a
+//! distinct `HostKeyError` instead of the generic "failed to connect" - so a
+//! caller (see `McpSshService::tool_ssh_connect`) can route a changed key
+//! through the same `ApprovalChannel` prompt that guards command execution,
+//! rather than silently trusting it or failing closed with no recourse.
+
+use anyhow::{Context, Result};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::sync::RwLock;
+
+/// How an unrecognized or changed host key is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HostKeyPolicy {
+    /// Reject any host whose key isn't already in the known-hosts store.
+    Strict,
+    /// Trust-on-first-use: record an unseen host's key, reject a changed one.
+    #[default]
+    AcceptNew,
+    /// Trust every host key, updating the stored fingerprint each time.
+    /// Equivalent to OpenSSH's `StrictHostKeyChecking=no`.
+    AcceptAll,
+}
+
+/// Why `KnownHostsStore::verify` rejected a connection.
+#[derive(Debug, Clone)]
+pub enum HostKeyError {
+    /// The host's key doesn't match what's on record - possibly a
+    /// man-in-the-middle, possibly the server was legitimately re-keyed.
+    Mismatch {
+        host: String,
+        port: u16,
+        expected: String,
+        actual: String,
+    },
+    /// `HostKeyPolicy::Strict` saw a host that isn't in the store yet.
+    Unknown {
+        host: String,
+        port: u16,
+        fingerprint: String,
+    },
+}
+
+impl std::fmt::Display for HostKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostKeyError::Mismatch {
+                host,
+                port,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Host key for {host}:{port} has changed! Expected {expected}, got {actual}. \
+                This could mean someone is intercepting the connection, or the server's key \
+                was legitimately replaced."
+            ),
+            HostKeyError::Unknown {
+                host,
+                port,
+                fingerprint,
+            } => write!(
+                f,
+                "Host key for {host}:{port} ({fingerprint}) is not in the known_hosts store, \
+                and host_key_policy is strict."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HostKeyError {}
+
+/// Default known_hosts path, used when `SshConfig::known_hosts_path` is `None`.
+pub fn default_known_hosts_path() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home)
+        .join(".config")
+        .join("gemini-co-cli")
+        .join("known_hosts")
+}
+
+/// A TOFU known-hosts store, persisted as `host:port fingerprint` lines.
+pub struct KnownHostsStore {
+    path: PathBuf,
+    entries: RwLock<HashMap<String, String>>,
+}
+
+impl KnownHostsStore {
+    /// Load `path`, starting empty if it doesn't exist yet.
+    pub async fn load(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let mut entries = HashMap::new();
+
+        match tokio::fs::File::open(&path).await {
+            Ok(file) => {
+                let mut lines = tokio::io::BufReader::new(file).lines();
+                while let Some(line) = lines.next_line().await? {
+                    let line = line.trim();
+                    if line.is_empty() || line.starts_with('#') {
+                        continue;
+                    }
+                    if let Some((host_port, fingerprint)) = line.split_once(' ') {
+                        entries.insert(host_port.to_string(), fingerprint.to_string());
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read known_hosts at {:?}", path))
+            }
+        }
+
+        Ok(Self {
+            path,
+            entries: RwLock::new(entries),
+        })
+    }
+
+    /// Check `fingerprint` for `host:port` against the store, applying
+    /// `policy` to an unseen or changed key.
+    pub async fn verify(
+        &self,
+        host: &str,
+        port: u16,
+        fingerprint: &str,
+        policy: HostKeyPolicy,
+    ) -> Result<(), HostKeyError> {
+        let key = format!("{host}:{port}");
+
+        if policy == HostKeyPolicy::AcceptAll {
+            let _ = self.record(&key, fingerprint).await;
+            return Ok(());
+        }
+
+        let stored = self.entries.read().await.get(&key).cloned();
+        match stored {
+            Some(stored) if stored == fingerprint => Ok(()),
+            Some(stored) => Err(HostKeyError::Mismatch {
+                host: host.to_string(),
+                port,
+                expected: stored,
+                actual: fingerprint.to_string(),
+            }),
+            None if policy == HostKeyPolicy::Strict => Err(HostKeyError::Unknown {
+                host: host.to_string(),
+                port,
+                fingerprint: fingerprint.to_string(),
+            }),
+            None => {
+                let _ = self.record(&key, fingerprint).await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Record (or overwrite) `fingerprint` for `key`, in memory and on disk.
+    async fn record(&self, key: &str, fingerprint: &str) -> Result<()> {
+        self.entries
+            .write()
+            .await
+            .insert(key.to_string(), fingerprint.to_string());
+        self.persist().await
+    }
+
+    /// Overwrite `path` with the current in-memory entries.
+    async fn persist(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            let _ = tokio::fs::create_dir_all(parent).await;
+        }
+
+        let entries = self.entries.read().await;
+        let mut contents = String::new();
+        for (key, fingerprint) in entries.iter() {
+            contents.push_str(key);
+            contents.push(' ');
+            contents.push_str(fingerprint);
+            contents.push('\n');
+        }
+        drop(entries);
+
+        tokio::fs::write(&self.path, contents)
+            .await
+            .with_context(|| format!("Failed to write known_hosts at {:?}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A known_hosts path under the OS temp dir that no test collides on.
+    fn scratch_path() -> PathBuf {
+        std::env::temp_dir().join(format!("known_hosts-test-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_verify_unseen_host_strict_rejects() {
+        let store = KnownHostsStore::load(scratch_path()).await.unwrap();
+
+        let result = store
+            .verify("example.com", 22, "fingerprint-a", HostKeyPolicy::Strict)
+            .await;
+
+        assert!(matches!(result, Err(HostKeyError::Unknown { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_verify_unseen_host_accept_new_records_and_trusts() {
+        let store = KnownHostsStore::load(scratch_path()).await.unwrap();
+
+        assert!(store
+            .verify("example.com", 22, "fingerprint-a", HostKeyPolicy::AcceptNew)
+            .await
+            .is_ok());
+
+        // The now-recorded fingerprint is trusted on a later check.
+        assert!(store
+            .verify("example.com", 22, "fingerprint-a", HostKeyPolicy::AcceptNew)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_matching_fingerprint_ok() {
+        let store = KnownHostsStore::load(scratch_path()).await.unwrap();
+        store
+            .verify("example.com", 22, "fingerprint-a", HostKeyPolicy::AcceptNew)
+            .await
+            .unwrap();
+
+        let result = store
+            .verify("example.com", 22, "fingerprint-a", HostKeyPolicy::Strict)
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_mismatched_fingerprint_rejects() {
+        let store = KnownHostsStore::load(scratch_path()).await.unwrap();
+        store
+            .verify("example.com", 22, "fingerprint-a", HostKeyPolicy::AcceptNew)
+            .await
+            .unwrap();
+
+        let result = store
+            .verify("example.com", 22, "fingerprint-b", HostKeyPolicy::AcceptNew)
+            .await;
+
+        match result {
+            Err(HostKeyError::Mismatch { expected, actual, .. }) => {
+                assert_eq!(expected, "fingerprint-a");
+                assert_eq!(actual, "fingerprint-b");
+            }
+            _ => panic!("expected Mismatch error"),
+        }
+    }
+}
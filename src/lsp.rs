@@ -0,0 +1,259 @@
+//! Framing and process management for relaying LSP JSON-RPC traffic.
+//!
+//! The Language Server Protocol frames each JSON-RPC message with an
+//! HTTP-style `Content-Length: N\r\n\r\n` header block followed by exactly
+//! `N` bytes of UTF-8 JSON body. This module turns a byte stream (the
+//! language server's stdout) into discrete JSON values, and does the reverse
+//! for stdin, so the WebSocket side of the relay in `websocket.rs` only ever
+//! deals with parsed `serde_json::Value`s.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::process::Stdio;
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+/// Where `file://` URIs point on each side of the tunnel: the browser/editor's
+/// logical workspace root vs. the path the spawned language server actually
+/// sees (e.g. inside an SSH remote or a container mount).
+#[derive(Debug, Clone)]
+pub struct RootMapping {
+    pub client_root: String,
+    pub server_root: String,
+}
+
+impl RootMapping {
+    /// Rewrite every `file://<client_root>/...` URI in `value` to
+    /// `file://<server_root>/...`, recursively. Applied to messages heading
+    /// from the browser to the language server.
+    pub fn to_server(&self, value: &mut Value) {
+        rewrite_uris(value, &self.client_root, &self.server_root);
+    }
+
+    /// The inverse of `to_server`, applied to messages coming back from the
+    /// language server before they reach the browser.
+    pub fn to_client(&self, value: &mut Value) {
+        rewrite_uris(value, &self.server_root, &self.client_root);
+    }
+}
+
+fn rewrite_uris(value: &mut Value, from_root: &str, to_root: &str) {
+    let from_prefix = format!("file://{}", from_root);
+    match value {
+        Value::String(s) => {
+            if let Some(rest) = s.strip_prefix(&from_prefix) {
+                *s = format!("file://{}{}", to_root, rest);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                rewrite_uris(item, from_root, to_root);
+            }
+        }
+        Value::Object(map) => {
+            for v in map.values_mut() {
+                rewrite_uris(v, from_root, to_root);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parser state for decoding the `Content-Length` header framing off a byte
+/// stream. A single `read()` may contain a partial header, several whole
+/// messages back to back, or a header split across two reads, so state must
+/// be kept between calls to `feed`.
+enum FramerState {
+    ReadingHeader,
+    ReadingBody(usize),
+}
+
+/// Incrementally decodes LSP's `Content-Length`-framed JSON-RPC messages off
+/// a rolling byte buffer fed from successive socket/pipe reads.
+pub struct LspFramer {
+    buffer: Vec<u8>,
+    state: FramerState,
+}
+
+impl LspFramer {
+    pub fn new() -> Self {
+        Self {
+            buffer: Vec::new(),
+            state: FramerState::ReadingHeader,
+        }
+    }
+
+    /// Feed newly read bytes in, returning every complete JSON-RPC message
+    /// body the buffer can now yield, oldest first. Leftover partial data
+    /// (a split header or an incomplete body) stays buffered for next time.
+    pub fn feed(&mut self, bytes: &[u8]) -> Result<Vec<Value>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+
+        loop {
+            match self.state {
+                FramerState::ReadingHeader => {
+                    let Some(header_end) = find_header_end(&self.buffer) else {
+                        break;
+                    };
+                    let header = std::str::from_utf8(&self.buffer[..header_end])
+                        .context("LSP header block was not valid UTF-8")?;
+                    let content_length = parse_content_length(header)?;
+                    self.buffer.drain(..header_end + 4);
+                    self.state = FramerState::ReadingBody(content_length);
+                }
+                FramerState::ReadingBody(len) => {
+                    if self.buffer.len() < len {
+                        break;
+                    }
+                    let body: Vec<u8> = self.buffer.drain(..len).collect();
+                    let value: Value = serde_json::from_slice(&body)
+                        .context("LSP message body was not valid JSON")?;
+                    messages.push(value);
+                    self.state = FramerState::ReadingHeader;
+                }
+            }
+        }
+
+        Ok(messages)
+    }
+}
+
+impl Default for LspFramer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Find where the `\r\n\r\n`-terminated header block ends, returning the
+/// index the header text stops at (exclusive of the blank line).
+fn find_header_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|w| w == b"\r\n\r\n")
+}
+
+fn parse_content_length(header: &str) -> Result<usize> {
+    for line in header.split("\r\n") {
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            return value
+                .trim()
+                .parse::<usize>()
+                .context("Invalid Content-Length value");
+        }
+    }
+    bail!("LSP header block had no Content-Length")
+}
+
+/// Encode a JSON-RPC message body as a `Content-Length`-framed LSP message,
+/// ready to be written straight to a language server's stdin.
+pub fn encode_frame(value: &Value) -> Result<Vec<u8>> {
+    let body = serde_json::to_vec(value).context("Failed to serialize LSP message")?;
+    let mut framed = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+    framed.extend_from_slice(&body);
+    Ok(framed)
+}
+
+/// A spawned language server process, relayed over stdio.
+pub struct LspServer {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: Option<ChildStdout>,
+}
+
+impl LspServer {
+    /// Spawn `command` with `args`, piping stdin/stdout for JSON-RPC relay.
+    /// stderr is discarded; language servers generally log diagnostics there
+    /// rather than anything a client needs.
+    pub fn spawn(command: &str, args: &[String]) -> Result<Self> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to spawn language server: {}", command))?;
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take();
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Take the stdin pipe for writing framed requests (can only be called once).
+    pub fn take_stdin(&mut self) -> Option<ChildStdin> {
+        self.stdin.take()
+    }
+
+    /// Take the stdout pipe for reading framed responses (can only be called once).
+    pub fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.stdout.take()
+    }
+
+    /// Terminate the language server process.
+    pub async fn kill(&mut self) {
+        let _ = self.child.kill().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_header_split_across_reads() {
+        let mut framer = LspFramer::new();
+        let body = br#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#;
+        let frame = format!("Content-Length: {}\r\n\r\n", body.len());
+
+        // Split the header itself mid-way through a read.
+        let (first, second) = frame.split_at(frame.len() / 2);
+        assert!(framer.feed(first.as_bytes()).unwrap().is_empty());
+
+        let mut rest = second.as_bytes().to_vec();
+        rest.extend_from_slice(body);
+        let messages = framer.feed(&rest).unwrap();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["method"], "initialize");
+    }
+
+    #[test]
+    fn test_feed_two_messages_in_one_call() {
+        let mut framer = LspFramer::new();
+        let first_body = br#"{"jsonrpc":"2.0","id":1,"method":"a"}"#;
+        let second_body = br#"{"jsonrpc":"2.0","id":2,"method":"b"}"#;
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(format!("Content-Length: {}\r\n\r\n", first_body.len()).as_bytes());
+        bytes.extend_from_slice(first_body);
+        bytes.extend_from_slice(format!("Content-Length: {}\r\n\r\n", second_body.len()).as_bytes());
+        bytes.extend_from_slice(second_body);
+
+        let messages = framer.feed(&bytes).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["method"], "a");
+        assert_eq!(messages[1]["method"], "b");
+    }
+
+    #[test]
+    fn test_feed_body_split_across_reads() {
+        let mut framer = LspFramer::new();
+        let body = br#"{"jsonrpc":"2.0","id":1,"method":"textDocument/didOpen"}"#;
+        let header = format!("Content-Length: {}\r\n\r\n", body.len());
+
+        let mut first = header.into_bytes();
+        let (body_first, body_second) = body.split_at(body.len() / 2);
+        first.extend_from_slice(body_first);
+
+        assert!(framer.feed(&first).unwrap().is_empty());
+
+        let messages = framer.feed(body_second).unwrap();
+
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["method"], "textDocument/didOpen");
+    }
+}
@@ -1,5 +1,9 @@
+mod forward;
 mod gemini;
+mod known_hosts;
+mod lsp;
 mod mcp;
+mod protocol;
 mod ssh;
 mod state;
 mod websocket;
@@ -12,8 +16,10 @@ use std::net::SocketAddr;
 use tower_http::{services::ServeDir, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::mcp::http::{mcp_handler, mcp_sse_handler};
-use crate::state::AppState;
+use crate::mcp::http::{
+    get_approval_policy_handler, mcp_handler, mcp_sse_handler, update_approval_policy_handler,
+};
+use crate::state::{session_ttl_from_env, spawn_session_reaper, AppState};
 
 #[tokio::main]
 async fn main() {
@@ -29,8 +35,25 @@ async fn main() {
     // Load environment variables
     dotenvy::dotenv().ok();
 
+    // Run as a stdio MCP server (for direct launch by an editor/agent)
+    // instead of the HTTP server when invoked as `gemini-co-cli --stdio`.
+    if std::env::args().any(|arg| arg == "--stdio") {
+        return mcp::stdio::run().await.expect("stdio MCP transport failed");
+    }
+
     // Create shared application state
     let app_state = AppState::new();
+    tracing::info!(
+        "Agent backend: {} ({})",
+        app_state.agent_backend.name,
+        app_state.agent_backend.command
+    );
+
+    // Reap sessions an abandoned browser tab left behind, so their SSH
+    // connections and MCP services don't accumulate forever.
+    let session_ttl = session_ttl_from_env();
+    tracing::info!("Session idle timeout: {:?}", session_ttl);
+    spawn_session_reaper(app_state.clone(), session_ttl);
 
     // Build the application routes
     let app = Router::new()
@@ -56,9 +79,14 @@ async fn main() {
             "/ws/commands/:session_id",
             get(websocket::command_approval_ws_handler),
         )
+        .route("/lsp/:session_id", get(websocket::lsp_ws_handler))
         // MCP server endpoints for Gemini CLI tool integration
         .route("/mcp/:session_id", post(mcp_handler))
         .route("/mcp/:session_id/events", get(mcp_sse_handler))
+        .route(
+            "/mcp/:session_id/policy",
+            get(get_approval_policy_handler).put(update_approval_policy_handler),
+        )
         .nest_service("/static", ServeDir::new("static"))
         .layer(TraceLayer::new_for_http())
         .with_state(app_state);
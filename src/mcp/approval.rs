@@ -4,12 +4,16 @@
 //! an event-driven system using broadcast and oneshot channels.
 
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::{broadcast, oneshot, Mutex};
 use uuid::Uuid;
 
+/// How many past events each session keeps for SSE replay after a reconnect.
+const EVENT_HISTORY_CAPACITY: usize = 256;
+
 /// Event sent to frontends when approval status changes.
 #[derive(Debug, Clone, Serialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -18,16 +22,53 @@ pub enum ApprovalEvent {
     CommandRequested {
         approval_id: String,
         command: String,
+        /// Named SSH connection the command targets, if any - lets a
+        /// multi-connection frontend show which host a pending approval
+        /// belongs to instead of parsing it out of `command`.
+        connection: Option<String>,
     },
     /// A command was approved.
     CommandApproved { approval_id: String },
-    /// A command was rejected.
-    CommandRejected { approval_id: String },
+    /// A command was rejected, optionally with a reason the user typed in.
+    CommandRejected {
+        approval_id: String,
+        reason: Option<String>,
+    },
+    /// The user (or a disconnecting client) explicitly canceled the request,
+    /// as distinct from a deliberate rejection.
+    CommandCanceled { approval_id: String },
+    /// No decision arrived before the approval timeout elapsed.
+    CommandTimedOut { approval_id: String },
+}
+
+/// An `ApprovalEvent` tagged with a monotonically increasing id, so SSE
+/// clients can resume from a `Last-Event-ID` after a dropped connection
+/// instead of silently losing events.
+#[derive(Debug, Clone)]
+pub struct RecordedEvent {
+    pub id: u64,
+    pub event: ApprovalEvent,
+}
+
+/// Outcome of a resolved approval request. Distinct from `ApprovalError`:
+/// this is how a request normally finishes (including a timeout), so
+/// `wait_for_approval` only errors on something actually going wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApprovalOutcome {
+    Approved,
+    /// Explicitly denied, with an optional reason the user typed in - lets
+    /// Gemini re-propose a safer command instead of just backing off.
+    Denied { reason: Option<String> },
+    /// The requesting client disconnected or navigated away, distinct from
+    /// a deliberate denial - Gemini should back off rather than retry.
+    Canceled,
+    /// No decision arrived before the approval timeout elapsed.
+    TimedOut,
 }
 
 /// Pending approval entry.
 struct PendingApproval {
-    response_tx: oneshot::Sender<bool>,
+    response_tx: oneshot::Sender<ApprovalOutcome>,
     command: String,
 }
 
@@ -42,7 +83,13 @@ pub struct ApprovalChannel {
     pending: Arc<Mutex<HashMap<Uuid, PendingApproval>>>,
 
     /// Broadcast channel for approval events to all connected frontends.
-    event_tx: broadcast::Sender<ApprovalEvent>,
+    event_tx: broadcast::Sender<RecordedEvent>,
+
+    /// Monotonically increasing id assigned to each emitted event.
+    next_event_id: AtomicU64,
+
+    /// Bounded ring buffer of recent events, for `Last-Event-ID` replay.
+    history: Arc<Mutex<VecDeque<RecordedEvent>>>,
 }
 
 impl ApprovalChannel {
@@ -52,23 +99,57 @@ impl ApprovalChannel {
         Self {
             pending: Arc::new(Mutex::new(HashMap::new())),
             event_tx,
+            next_event_id: AtomicU64::new(1),
+            history: Arc::new(Mutex::new(VecDeque::with_capacity(EVENT_HISTORY_CAPACITY))),
         }
     }
 
     /// Subscribe to approval events.
     ///
     /// Called by WebSocket handlers to receive events for the frontend.
-    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalEvent> {
+    pub fn subscribe(&self) -> broadcast::Receiver<RecordedEvent> {
         self.event_tx.subscribe()
     }
 
+    /// Events with id greater than `after_id`, oldest first, from the
+    /// bounded history buffer. Used to replay events a reconnecting SSE
+    /// client missed, keyed off its `Last-Event-ID` header.
+    pub async fn events_since(&self, after_id: u64) -> Vec<RecordedEvent> {
+        let history = self.history.lock().await;
+        history
+            .iter()
+            .filter(|e| e.id > after_id)
+            .cloned()
+            .collect()
+    }
+
+    /// Assign the next event id, record it in history, and broadcast it.
+    async fn emit(&self, event: ApprovalEvent) {
+        let id = self.next_event_id.fetch_add(1, Ordering::SeqCst);
+        let recorded = RecordedEvent { id, event };
+
+        {
+            let mut history = self.history.lock().await;
+            if history.len() >= EVENT_HISTORY_CAPACITY {
+                history.pop_front();
+            }
+            history.push_back(recorded.clone());
+        }
+
+        let _ = self.event_tx.send(recorded);
+    }
+
     /// Request approval for a command.
     ///
     /// This broadcasts the request to all connected frontends and returns
-    /// a future that resolves when the user approves or rejects.
+    /// a future that resolves when the user approves, rejects, or cancels.
     ///
     /// Returns the approval ID and a receiver for the decision.
-    pub async fn request_approval(&self, command: String) -> (Uuid, oneshot::Receiver<bool>) {
+    pub async fn request_approval(
+        &self,
+        command: String,
+        connection: Option<String>,
+    ) -> (Uuid, oneshot::Receiver<ApprovalOutcome>) {
         let id = Uuid::new_v4();
         let (tx, rx) = oneshot::channel();
 
@@ -85,75 +166,161 @@ impl ApprovalChannel {
         }
 
         // Broadcast the request to all frontends
-        let _ = self.event_tx.send(ApprovalEvent::CommandRequested {
+        self.emit(ApprovalEvent::CommandRequested {
             approval_id: id.to_string(),
             command,
-        });
+            connection,
+        })
+        .await;
 
         (id, rx)
     }
 
-    /// Submit an approval decision.
+    /// Submit an explicit approve/reject decision. `reason`, if given on a
+    /// rejection, is shown to the user and handed back to Gemini so it can
+    /// re-propose a safer command instead of just giving up.
     ///
     /// Called by the WebSocket handler when the user approves or rejects.
-    /// Returns true if the decision was delivered, false if approval ID not found.
-    pub async fn submit_decision(&self, approval_id: Uuid, approved: bool) -> bool {
+    /// Returns the original command text if the decision was delivered (so
+    /// callers can e.g. feed it to `ApprovalPolicy::remember`), or `None` if
+    /// the approval ID wasn't found (e.g. it already timed out or was
+    /// canceled).
+    pub async fn submit_decision(
+        &self,
+        approval_id: Uuid,
+        approved: bool,
+        reason: Option<String>,
+    ) -> Option<String> {
+        let outcome = if approved {
+            ApprovalOutcome::Approved
+        } else {
+            ApprovalOutcome::Denied {
+                reason: reason.clone(),
+            }
+        };
+        let event = if approved {
+            ApprovalEvent::CommandApproved {
+                approval_id: approval_id.to_string(),
+            }
+        } else {
+            ApprovalEvent::CommandRejected {
+                approval_id: approval_id.to_string(),
+                reason,
+            }
+        };
+        self.resolve(approval_id, outcome, event).await
+    }
+
+    /// Cancel a pending approval, distinct from a deliberate rejection
+    /// (e.g. the requesting client disconnected or navigated away).
+    pub async fn submit_cancel(&self, approval_id: Uuid) -> bool {
+        self.resolve(
+            approval_id,
+            ApprovalOutcome::Canceled,
+            ApprovalEvent::CommandCanceled {
+                approval_id: approval_id.to_string(),
+            },
+        )
+        .await
+        .is_some()
+    }
+
+    /// Resolve a pending approval with an outcome, broadcasting `event` and
+    /// delivering `outcome` to the waiting MCP tool call. Returns the
+    /// pending approval's command text if one was actually delivered.
+    async fn resolve(
+        &self,
+        approval_id: Uuid,
+        outcome: ApprovalOutcome,
+        event: ApprovalEvent,
+    ) -> Option<String> {
         let pending_approval = {
             let mut pending = self.pending.lock().await;
             pending.remove(&approval_id)
         };
 
-        if let Some(approval) = pending_approval {
-            // Broadcast the decision to all frontends
-            let event = if approved {
-                ApprovalEvent::CommandApproved {
-                    approval_id: approval_id.to_string(),
-                }
-            } else {
-                ApprovalEvent::CommandRejected {
-                    approval_id: approval_id.to_string(),
-                }
-            };
-            let _ = self.event_tx.send(event);
-
-            // Send to the waiting MCP tool
-            approval.response_tx.send(approved).is_ok()
-        } else {
-            false
+        let approval = pending_approval?;
+        self.emit(event).await;
+        if approval.response_tx.send(outcome).is_err() {
+            return None;
         }
+        Some(approval.command)
+    }
+
+    /// Auto-resolve `command` per policy (an allow/deny-list match or a
+    /// remembered decision) without creating a pending approval - the
+    /// request never blocks on user input, but it's still recorded through
+    /// the same `CommandRequested`/`CommandApproved`/`CommandRejected`
+    /// events an interactive decision would emit, so the audit trail
+    /// (history/SSE replay) covers it identically.
+    pub async fn record_auto_decision(
+        &self,
+        command: String,
+        approved: bool,
+        connection: Option<String>,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        self.emit(ApprovalEvent::CommandRequested {
+            approval_id: id.to_string(),
+            command,
+            connection,
+        })
+        .await;
+        let event = if approved {
+            ApprovalEvent::CommandApproved {
+                approval_id: id.to_string(),
+            }
+        } else {
+            ApprovalEvent::CommandRejected {
+                approval_id: id.to_string(),
+                reason: Some("Denied by approval policy (deny-list or remembered decision).".to_string()),
+            }
+        };
+        self.emit(event).await;
+        id
+    }
+
+    /// Mark a pending approval as timed out: removes it, and broadcasts
+    /// `CommandTimedOut` so the UI can show "timed out" instead of "denied".
+    async fn mark_timed_out(&self, approval_id: Uuid) {
+        let mut pending = self.pending.lock().await;
+        pending.remove(&approval_id);
+        drop(pending);
+        self.emit(ApprovalEvent::CommandTimedOut {
+            approval_id: approval_id.to_string(),
+        })
+        .await;
     }
 
     /// Wait for approval with timeout.
     ///
-    /// Convenience method that handles the common case of waiting for approval.
+    /// Convenience method that handles the common case of waiting for
+    /// approval. A timeout resolves to `Ok(ApprovalOutcome::TimedOut)`
+    /// (and emits `CommandTimedOut`) rather than an error, since it's a
+    /// normal way for a request to finish; `Err` is reserved for the
+    /// oneshot channel dropping unexpectedly.
     pub async fn wait_for_approval(
         &self,
         command: String,
         timeout: Duration,
-    ) -> Result<bool, ApprovalError> {
-        let (id, rx) = self.request_approval(command).await;
+        connection: Option<String>,
+    ) -> Result<ApprovalOutcome, ApprovalError> {
+        let (id, rx) = self.request_approval(command, connection).await;
 
         match tokio::time::timeout(timeout, rx).await {
-            Ok(Ok(approved)) => Ok(approved),
+            Ok(Ok(outcome)) => Ok(outcome),
             Ok(Err(_)) => {
                 // Channel was dropped (shouldn't happen normally)
-                self.cleanup_pending(id).await;
+                self.mark_timed_out(id).await;
                 Err(ApprovalError::ChannelClosed)
             }
             Err(_) => {
-                // Timeout
-                self.cleanup_pending(id).await;
-                Err(ApprovalError::Timeout)
+                self.mark_timed_out(id).await;
+                Ok(ApprovalOutcome::TimedOut)
             }
         }
     }
 
-    /// Clean up a pending approval (e.g., on timeout).
-    async fn cleanup_pending(&self, id: Uuid) {
-        let mut pending = self.pending.lock().await;
-        pending.remove(&id);
-    }
-
     /// Get the number of pending approvals.
     pub async fn pending_count(&self) -> usize {
         self.pending.lock().await.len()
@@ -166,11 +333,11 @@ impl Default for ApprovalChannel {
     }
 }
 
-/// Errors that can occur during approval.
-#[derive(Debug, Clone)]
+/// Errors that can occur during approval. A timeout is not one of these -
+/// it's a normal `ApprovalOutcome`, since that's how most unattended
+/// approvals actually resolve.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ApprovalError {
-    /// The approval request timed out.
-    Timeout,
     /// The approval channel was closed unexpectedly.
     ChannelClosed,
 }
@@ -178,7 +345,6 @@ pub enum ApprovalError {
 impl std::fmt::Display for ApprovalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ApprovalError::Timeout => write!(f, "Approval request timed out"),
             ApprovalError::ChannelClosed => write!(f, "Approval channel closed unexpectedly"),
         }
     }
@@ -196,37 +362,121 @@ mod tests {
         let mut subscriber = channel.subscribe();
 
         // Request approval
-        let (id, rx) = channel.request_approval("ls -la".to_string()).await;
+        let (id, rx) = channel
+            .request_approval("ls -la".to_string(), Some("prod-1".to_string()))
+            .await;
 
         // Check event was broadcast
-        let event = subscriber.try_recv().unwrap();
-        match event {
+        let recorded = subscriber.try_recv().unwrap();
+        assert_eq!(recorded.id, 1);
+        match recorded.event {
             ApprovalEvent::CommandRequested {
                 approval_id,
                 command,
+                connection,
             } => {
                 assert_eq!(approval_id, id.to_string());
                 assert_eq!(command, "ls -la");
+                assert_eq!(connection.as_deref(), Some("prod-1"));
             }
             _ => panic!("Expected CommandRequested event"),
         }
 
         // Submit approval
-        let delivered = channel.submit_decision(id, true).await;
-        assert!(delivered);
+        let delivered = channel.submit_decision(id, true, None).await;
+        assert_eq!(delivered.as_deref(), Some("ls -la"));
 
         // Check the receiver got the decision
-        assert_eq!(rx.await.unwrap(), true);
+        assert_eq!(rx.await.unwrap(), ApprovalOutcome::Approved);
     }
 
     #[tokio::test]
     async fn test_rejection_flow() {
         let channel = ApprovalChannel::new();
 
-        let (id, rx) = channel.request_approval("rm -rf /".to_string()).await;
+        let (id, rx) = channel.request_approval("rm -rf /".to_string(), None).await;
+
+        channel
+            .submit_decision(id, false, Some("too dangerous".to_string()))
+            .await;
+
+        assert_eq!(
+            rx.await.unwrap(),
+            ApprovalOutcome::Denied {
+                reason: Some("too dangerous".to_string())
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_flow() {
+        let channel = ApprovalChannel::new();
+        let mut subscriber = channel.subscribe();
+
+        let (id, rx) = channel.request_approval("top".to_string(), None).await;
+        subscriber.try_recv().unwrap(); // drain CommandRequested
+
+        let delivered = channel.submit_cancel(id).await;
+        assert!(delivered);
+        assert_eq!(rx.await.unwrap(), ApprovalOutcome::Canceled);
+
+        let recorded = subscriber.try_recv().unwrap();
+        assert!(matches!(recorded.event, ApprovalEvent::CommandCanceled { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_flow() {
+        let channel = ApprovalChannel::new();
+        let mut subscriber = channel.subscribe();
+
+        let result = channel
+            .wait_for_approval("sleep 100".to_string(), Duration::from_millis(10), None)
+            .await;
+
+        assert_eq!(result, Ok(ApprovalOutcome::TimedOut));
+        subscriber.try_recv().unwrap(); // drain CommandRequested
+        let recorded = subscriber.try_recv().unwrap();
+        assert!(matches!(recorded.event, ApprovalEvent::CommandTimedOut { .. }));
+        assert_eq!(channel.pending_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_record_auto_decision() {
+        let channel = ApprovalChannel::new();
+        let mut subscriber = channel.subscribe();
+
+        let id = channel
+            .record_auto_decision("git status".to_string(), true, None)
+            .await;
+
+        let requested = subscriber.try_recv().unwrap();
+        match requested.event {
+            ApprovalEvent::CommandRequested { approval_id, .. } => {
+                assert_eq!(approval_id, id.to_string())
+            }
+            _ => panic!("Expected CommandRequested event"),
+        }
+        let resolved = subscriber.try_recv().unwrap();
+        assert!(matches!(resolved.event, ApprovalEvent::CommandApproved { .. }));
+        // No pending entry was ever created, so there's nothing to resolve later.
+        assert_eq!(channel.pending_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_replay_since() {
+        let channel = ApprovalChannel::new();
+
+        let (id, _rx) = channel.request_approval("ls".to_string(), None).await;
+        channel.submit_decision(id, true, None).await;
 
-        channel.submit_decision(id, false).await;
+        // Nothing missed yet.
+        assert!(channel.events_since(2).await.is_empty());
 
-        assert_eq!(rx.await.unwrap(), false);
+        // A client that last saw event 0 should get both recorded events
+        // (CommandRequested, then CommandApproved) replayed in order.
+        let replay = channel.events_since(0).await;
+        assert_eq!(replay.len(), 2);
+        assert_eq!(replay[0].id, 1);
+        assert_eq!(replay[1].id, 2);
     }
 }
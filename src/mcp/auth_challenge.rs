@@ -0,0 +1,207 @@
+//! Event-driven channel for keyboard-interactive (PAM/MFA) SSH auth
+//! prompts, mirroring `ApprovalChannel`'s request/decision shape but for
+//! auth prompt rounds instead of command approvals. Kept separate from
+//! `ApprovalChannel` since the two have different lifecycles: a challenge
+//! only exists for the duration of one `ssh_connect` call, while approvals
+//! span the whole session, so there's no need for the history/replay
+//! buffer `ApprovalChannel` keeps for SSE reconnects.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, oneshot, Mutex};
+use uuid::Uuid;
+
+use crate::ssh::AuthPrompt;
+use serde::Serialize;
+
+/// Event broadcast to frontends as a keyboard-interactive auth round runs.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthChallengeEvent {
+    /// The server issued one or more prompts for the current round.
+    PromptRequested {
+        challenge_id: String,
+        name: String,
+        instructions: String,
+        prompts: Vec<AuthPrompt>,
+    },
+    /// The user answered the current round.
+    PromptAnswered { challenge_id: String },
+}
+
+/// Outcome of a pending challenge round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthChallengeOutcome {
+    Answered(Vec<String>),
+    /// No answer arrived before the round's timeout, mirroring
+    /// `ApprovalOutcome::TimedOut`'s "resolve, don't error" handling.
+    TimedOut,
+}
+
+/// Pending challenge round entry.
+struct PendingChallenge {
+    response_tx: oneshot::Sender<AuthChallengeOutcome>,
+}
+
+/// Channel for keyboard-interactive auth prompts, following the same
+/// broadcast-request/oneshot-response shape as `ApprovalChannel`:
+/// - `SshSession::connect` (via `McpSshService::tool_ssh_connect`) calls
+///   `request_answers()`, which blocks until the frontend answers or the
+///   round times out.
+/// - WebSocket handlers subscribe to events via `subscribe()`.
+/// - The frontend answers through `submit_answers()`.
+pub struct AuthChallengeChannel {
+    pending: Arc<Mutex<HashMap<Uuid, PendingChallenge>>>,
+    event_tx: broadcast::Sender<AuthChallengeEvent>,
+}
+
+impl AuthChallengeChannel {
+    pub fn new() -> Self {
+        let (event_tx, _) = broadcast::channel(16);
+        Self {
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            event_tx,
+        }
+    }
+
+    /// Subscribe to auth challenge events.
+    pub fn subscribe(&self) -> broadcast::Receiver<AuthChallengeEvent> {
+        self.event_tx.subscribe()
+    }
+
+    /// Broadcast a prompt round and wait up to `timeout` for the frontend
+    /// to answer it. A timeout resolves to `AuthChallengeOutcome::TimedOut`
+    /// rather than an error, the same way `ApprovalChannel::wait_for_approval`
+    /// treats its timeout as a normal outcome.
+    pub async fn request_answers(
+        &self,
+        name: String,
+        instructions: String,
+        prompts: Vec<AuthPrompt>,
+        timeout: Duration,
+    ) -> AuthChallengeOutcome {
+        let id = Uuid::new_v4();
+        let (tx, rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .await
+            .insert(id, PendingChallenge { response_tx: tx });
+
+        let _ = self.event_tx.send(AuthChallengeEvent::PromptRequested {
+            challenge_id: id.to_string(),
+            name,
+            instructions,
+            prompts,
+        });
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(outcome)) => outcome,
+            Ok(Err(_)) | Err(_) => {
+                self.pending.lock().await.remove(&id);
+                AuthChallengeOutcome::TimedOut
+            }
+        }
+    }
+
+    /// Submit the user's answers for a pending challenge round. Returns
+    /// whether a pending round was actually found - it may already have
+    /// timed out.
+    pub async fn submit_answers(&self, challenge_id: Uuid, answers: Vec<String>) -> bool {
+        let pending = self.pending.lock().await.remove(&challenge_id);
+        let Some(pending) = pending else {
+            return false;
+        };
+        let _ = self.event_tx.send(AuthChallengeEvent::PromptAnswered {
+            challenge_id: challenge_id.to_string(),
+        });
+        pending
+            .response_tx
+            .send(AuthChallengeOutcome::Answered(answers))
+            .is_ok()
+    }
+
+    /// Number of challenge rounds currently awaiting an answer.
+    pub async fn pending_count(&self) -> usize {
+        self.pending.lock().await.len()
+    }
+}
+
+impl Default for AuthChallengeChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_answer_flow() {
+        let channel = AuthChallengeChannel::new();
+        let mut subscriber = channel.subscribe();
+
+        let prompts = vec![AuthPrompt {
+            prompt: "Verification code: ".to_string(),
+            echo: false,
+        }];
+
+        let channel_for_answer = Arc::new(channel);
+        let waiter = channel_for_answer.clone();
+        let wait = tokio::spawn(async move {
+            waiter
+                .request_answers(
+                    "otp".to_string(),
+                    "Enter your TOTP code".to_string(),
+                    prompts,
+                    Duration::from_secs(5),
+                )
+                .await
+        });
+
+        let requested = subscriber.recv().await.unwrap();
+        let challenge_id = match requested {
+            AuthChallengeEvent::PromptRequested { challenge_id, .. } => challenge_id,
+            _ => panic!("Expected PromptRequested event"),
+        };
+        let id = Uuid::parse_str(&challenge_id).unwrap();
+
+        let delivered = channel_for_answer
+            .submit_answers(id, vec!["123456".to_string()])
+            .await;
+        assert!(delivered);
+
+        assert_eq!(
+            wait.await.unwrap(),
+            AuthChallengeOutcome::Answered(vec!["123456".to_string()])
+        );
+
+        let answered = subscriber.recv().await.unwrap();
+        assert!(matches!(answered, AuthChallengeEvent::PromptAnswered { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_flow() {
+        let channel = AuthChallengeChannel::new();
+
+        let outcome = channel
+            .request_answers(
+                "otp".to_string(),
+                String::new(),
+                vec![],
+                Duration::from_millis(10),
+            )
+            .await;
+
+        assert_eq!(outcome, AuthChallengeOutcome::TimedOut);
+        assert_eq!(channel.pending_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_submit_unknown_challenge() {
+        let channel = AuthChallengeChannel::new();
+        let delivered = channel.submit_answers(Uuid::new_v4(), vec![]).await;
+        assert!(!delivered);
+    }
+}
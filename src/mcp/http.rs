@@ -3,7 +3,8 @@
 //! Provides JSON-RPC and SSE endpoints for MCP tool communication.
 //! Uses rmcp 0.12.0 model types for MCP-compliant responses.
 
-use crate::mcp::approval::ApprovalEvent;
+use crate::mcp::approval::{ApprovalEvent, RecordedEvent};
+use crate::mcp::{ApprovalPolicy, McpSshService, ToolStep};
 use crate::state::AppState;
 use axum::{
     extract::{Path, State},
@@ -26,16 +27,16 @@ use uuid::Uuid;
 #[derive(Debug, Deserialize)]
 pub struct JsonRpcRequest {
     #[allow(dead_code)]
-    jsonrpc: String,
-    method: String,
+    pub(crate) jsonrpc: String,
+    pub(crate) method: String,
     #[serde(default)]
-    params: Option<Value>,
-    id: Option<Value>,
+    pub(crate) params: Option<Value>,
+    pub(crate) id: Option<Value>,
 }
 
 /// JSON-RPC response structure.
 #[derive(Debug, Serialize)]
-struct JsonRpcResponse {
+pub(crate) struct JsonRpcResponse {
     jsonrpc: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     result: Option<Value>,
@@ -53,7 +54,7 @@ struct JsonRpcError {
 }
 
 impl JsonRpcResponse {
-    fn success(id: Option<Value>, result: Value) -> Self {
+    pub(crate) fn success(id: Option<Value>, result: Value) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             result: Some(result),
@@ -62,7 +63,7 @@ impl JsonRpcResponse {
         }
     }
 
-    fn error(id: Option<Value>, code: i32, message: String) -> Self {
+    pub(crate) fn error(id: Option<Value>, code: i32, message: String) -> Self {
         Self {
             jsonrpc: "2.0".to_string(),
             result: None,
@@ -74,44 +75,35 @@ impl JsonRpcResponse {
             id,
         }
     }
-}
-
-/// Handle MCP JSON-RPC requests.
-///
-/// POST /mcp/:session_id
-pub async fn mcp_handler(
-    Path(session_id): Path<String>,
-    State(app_state): State<AppState>,
-    Json(request): Json<JsonRpcRequest>,
-) -> Response {
-    let session_uuid = match Uuid::parse_str(&session_id) {
-        Ok(id) => id,
-        Err(_) => {
-            return Json(JsonRpcResponse::error(
-                request.id,
-                -32600,
-                "Invalid session ID".to_string(),
-            ))
-            .into_response();
-        }
-    };
 
-    let services = app_state.mcp_services.read().await;
-    let service = match services.get(&session_uuid) {
-        Some(s) => s.clone(),
-        None => {
-            return Json(JsonRpcResponse::error(
-                request.id,
-                -32001,
-                "Session not found".to_string(),
-            ))
-            .into_response();
+    /// Build the JSON-RPC error returned when a session's token bucket is empty.
+    pub(crate) fn rate_limited(id: Option<Value>, retry_after_ms: u64) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            result: None,
+            error: Some(JsonRpcError {
+                code: -32005,
+                message: "Rate limit exceeded".to_string(),
+                data: Some(json!({ "retryAfterMs": retry_after_ms })),
+            }),
+            id,
         }
-    };
-    drop(services);
+    }
+}
 
-    // Handle different MCP methods
-    let response = match request.method.as_str() {
+/// Dispatch a single JSON-RPC request against an MCP service.
+///
+/// Shared by every transport (`mcp_handler`'s HTTP endpoint, the stdio
+/// transport in `mcp::stdio`) so `initialize`/`tools/list`/`tools/call`/
+/// `notifications/initialized` behave identically regardless of how the
+/// bytes arrived. Per-session rate limiting is applied by the caller before
+/// invoking this, since it's specific to the `tools/call` method and to
+/// transports that share a server across sessions (i.e. HTTP).
+pub(crate) async fn handle_rpc(
+    service: &McpSshService,
+    request: JsonRpcRequest,
+) -> JsonRpcResponse {
+    match request.method.as_str() {
         "initialize" => {
             let info = service.get_server_info();
             JsonRpcResponse::success(
@@ -134,7 +126,7 @@ pub async fn mcp_handler(
                     json!({
                         "name": t.name,
                         "description": t.description,
-                        "inputSchema": t.input_schema
+                        "inputSchema": t.input_schema,
                     })
                 })
                 .collect();
@@ -144,6 +136,29 @@ pub async fn mcp_handler(
 
         "tools/call" => {
             let params = request.params.unwrap_or(json!({}));
+
+            // A caller may chain several tool calls in one request (bounded by
+            // McpSshService::max_chain_steps); otherwise it's a single call.
+            if let Some(chain) = params.get("chain").and_then(|v| v.as_array()) {
+                let steps: Vec<ToolStep> = chain
+                    .iter()
+                    .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                    .collect();
+
+                let results = service.call_tool_chain(steps).await;
+                let results_json: Vec<Value> = results
+                    .iter()
+                    .map(|r| {
+                        json!({
+                            "content": serde_json::to_value(&r.content).unwrap_or_default(),
+                            "isError": r.is_error.unwrap_or(false)
+                        })
+                    })
+                    .collect();
+
+                return JsonRpcResponse::success(request.id, json!({ "results": results_json }));
+            }
+
             let tool_name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
             let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
 
@@ -171,17 +186,139 @@ pub async fn mcp_handler(
             -32601,
             format!("Method not found: {}", request.method),
         ),
+    }
+}
+
+/// Handle MCP JSON-RPC requests.
+/// JSON-RPC 2.0 permits a single request object or a batch array of them.
+///
+/// POST /mcp/:session_id
+pub async fn mcp_handler(
+    Path(session_id): Path<String>,
+    State(app_state): State<AppState>,
+    Json(body): Json<Value>,
+) -> Response {
+    let session_uuid = match Uuid::parse_str(&session_id) {
+        Ok(id) => id,
+        Err(_) => {
+            return Json(JsonRpcResponse::error(
+                None,
+                -32600,
+                "Invalid session ID".to_string(),
+            ))
+            .into_response();
+        }
+    };
+
+    let services = app_state.mcp_services.read().await;
+    let service = match services.get(&session_uuid) {
+        Some(s) => s.clone(),
+        None => {
+            return Json(JsonRpcResponse::error(
+                None,
+                -32001,
+                "Session not found".to_string(),
+            ))
+            .into_response();
+        }
     };
+    drop(services);
+
+    match body {
+        Value::Array(items) => {
+            let mut responses = Vec::with_capacity(items.len());
+            for item in items {
+                // A malformed entry still gets a parse-error response so the
+                // batch's shape matches the request's, per JSON-RPC 2.0.
+                let request: JsonRpcRequest = match serde_json::from_value(item) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        responses.push(JsonRpcResponse::error(
+                            None,
+                            -32700,
+                            format!("Parse error: {}", e),
+                        ));
+                        continue;
+                    }
+                };
+                // Notifications (no `id`) get no response entry in the batch.
+                let is_notification = request.id.is_none();
+                let response = dispatch_one(&app_state, &service, session_uuid, request).await;
+                if !is_notification {
+                    responses.push(response);
+                }
+            }
+            Json(responses).into_response()
+        }
+        _ => {
+            let request: JsonRpcRequest = match serde_json::from_value(body) {
+                Ok(r) => r,
+                Err(e) => {
+                    return Json(JsonRpcResponse::error(
+                        None,
+                        -32700,
+                        format!("Parse error: {}", e),
+                    ))
+                    .into_response();
+                }
+            };
+            Json(dispatch_one(&app_state, &service, session_uuid, request).await).into_response()
+        }
+    }
+}
+
+/// Apply rate limiting to `tools/call` and dispatch through `handle_rpc`.
+async fn dispatch_one(
+    app_state: &AppState,
+    service: &McpSshService,
+    session_uuid: Uuid,
+    request: JsonRpcRequest,
+) -> JsonRpcResponse {
+    if request.method == "tools/call" {
+        let decision = app_state.mcp_rate_limiter.check(session_uuid);
+        if !decision.allowed {
+            return JsonRpcResponse::rate_limited(
+                request.id,
+                decision.retry_after.as_millis() as u64,
+            );
+        }
+    }
+
+    handle_rpc(service, request).await
+}
+
+fn approval_event_type(event: &ApprovalEvent) -> &'static str {
+    match event {
+        ApprovalEvent::CommandRequested { .. } => "command_requested",
+        ApprovalEvent::CommandApproved { .. } => "command_approved",
+        ApprovalEvent::CommandRejected { .. } => "command_rejected",
+        ApprovalEvent::CommandCanceled { .. } => "command_canceled",
+        ApprovalEvent::CommandTimedOut { .. } => "command_timed_out",
+    }
+}
 
-    Json(response).into_response()
+fn recorded_event_to_sse(recorded: RecordedEvent) -> Option<Event> {
+    let event_json = serde_json::to_string(&recorded.event).ok()?;
+    Some(
+        Event::default()
+            .id(recorded.id.to_string())
+            .event(approval_event_type(&recorded.event))
+            .data(event_json),
+    )
 }
 
 /// Handle MCP SSE event stream for approval events.
 ///
 /// GET /mcp/:session_id/events
+///
+/// Resumable: each `ApprovalEvent` is assigned a monotonically increasing
+/// id. A client reconnecting with a `Last-Event-ID` header gets buffered
+/// events after that id replayed before the stream switches to live events,
+/// so a dropped connection no longer silently loses approval events.
 pub async fn mcp_sse_handler(
     Path(session_id): Path<String>,
     State(app_state): State<AppState>,
+    headers: axum::http::HeaderMap,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, StatusCode> {
     let session_uuid = Uuid::parse_str(&session_id).map_err(|_| StatusCode::BAD_REQUEST)?;
 
@@ -192,22 +329,89 @@ pub async fn mcp_sse_handler(
         .ok_or(StatusCode::NOT_FOUND)?;
     drop(services);
 
-    // Subscribe to approval events
+    let last_event_id: u64 = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+
+    // Subscribe first so nothing emitted after this point is missed, then
+    // pull the replay buffer — any overlap with the live stream is a
+    // harmless duplicate since clients key off the SSE id.
     let receiver = service.approval_channel.subscribe();
-    let stream = BroadcastStream::new(receiver);
-
-    let event_stream = stream.filter_map(|result| match result {
-        Ok(event) => {
-            let event_json = serde_json::to_string(&event).ok()?;
-            let event_type = match &event {
-                ApprovalEvent::CommandRequested { .. } => "command_requested",
-                ApprovalEvent::CommandApproved { .. } => "command_approved",
-                ApprovalEvent::CommandRejected { .. } => "command_rejected",
-            };
-            Some(Ok(Event::default().event(event_type).data(event_json)))
-        }
-        Err(_) => None, // Skip lagged messages
+    let replay = service.approval_channel.events_since(last_event_id).await;
+
+    let replay_stream = tokio_stream::iter(replay).filter_map(|recorded| {
+        recorded_event_to_sse(recorded).map(Ok::<_, Infallible>)
     });
 
-    Ok(Sse::new(event_stream))
+    let live_stream = BroadcastStream::new(receiver).filter_map(|result| {
+        result
+            .ok()
+            .and_then(recorded_event_to_sse)
+            .map(Ok::<_, Infallible>)
+    });
+
+    let approval_stream = futures::stream::StreamExt::chain(replay_stream, live_stream);
+
+    // Subscribe to tool-chain progress events, so a multi-step `tools/call`
+    // shows intermediate progress on the same stream.
+    let progress_receiver = service.subscribe_progress();
+    let progress_stream =
+        BroadcastStream::new(progress_receiver).filter_map(|result| match result {
+            Ok(event) => {
+                let event_json = serde_json::to_string(&event).ok()?;
+                Some(Ok(Event::default().event("tool_progress").data(event_json)))
+            }
+            Err(_) => None, // Skip lagged messages
+        });
+
+    // Subscribe to streamed `ssh_shell` output, so interactive PTY sessions
+    // show up on the same stream instead of needing a separate poll.
+    let shell_output_receiver = service.subscribe_shell_output();
+    let shell_output_stream =
+        BroadcastStream::new(shell_output_receiver).filter_map(|result| match result {
+            Ok(event) => {
+                let event_json = serde_json::to_string(&event).ok()?;
+                Some(Ok(Event::default().event("ssh_shell_output").data(event_json)))
+            }
+            Err(_) => None, // Skip lagged messages
+        });
+
+    Ok(Sse::new(
+        approval_stream.merge(progress_stream).merge(shell_output_stream),
+    ))
+}
+
+/// View the session's active `ApprovalPolicy`.
+///
+/// GET /mcp/:session_id/policy
+pub async fn get_approval_policy_handler(
+    Path(session_id): Path<String>,
+    State(app_state): State<AppState>,
+) -> Result<Json<ApprovalPolicy>, StatusCode> {
+    let session_uuid = Uuid::parse_str(&session_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let session = app_state
+        .get_session(session_uuid)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(session.policy.read().await.clone()))
+}
+
+/// Replace the session's active `ApprovalPolicy` wholesale (allow/deny
+/// patterns, remembered decisions, default timeout).
+///
+/// PUT /mcp/:session_id/policy
+pub async fn update_approval_policy_handler(
+    Path(session_id): Path<String>,
+    State(app_state): State<AppState>,
+    Json(policy): Json<ApprovalPolicy>,
+) -> Result<Json<ApprovalPolicy>, StatusCode> {
+    let session_uuid = Uuid::parse_str(&session_id).map_err(|_| StatusCode::BAD_REQUEST)?;
+    let session = app_state
+        .get_session(session_uuid)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    *session.policy.write().await = policy.clone();
+    Ok(Json(policy))
 }
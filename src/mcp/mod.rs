@@ -5,9 +5,15 @@
 //! "EXECUTE:" text pattern parsing with type-safe JSON-RPC.
 
 mod approval;
+mod auth_challenge;
 pub mod http;
+mod policy;
+pub mod rate_limit;
 mod server;
-mod tools;
+pub mod stdio;
 
-pub use approval::{ApprovalChannel, ApprovalEvent};
-pub use server::McpSshService;
+pub use approval::{ApprovalChannel, ApprovalEvent, ApprovalOutcome, RecordedEvent};
+pub use auth_challenge::{AuthChallengeChannel, AuthChallengeEvent, AuthChallengeOutcome};
+pub use policy::{ApprovalPolicy, PolicyDecision};
+pub use rate_limit::{RateLimitConfig, RateLimiter};
+pub use server::{McpSshService, ToolStep, CONNECTION_IDLE_TIMEOUT};
@@ -0,0 +1,159 @@
+//! Per-session approval policy: allow/deny glob lists and remembered
+//! "always allow"/"always deny" decisions, consulted before a
+//! side-effecting tool call is routed through the interactive
+//! `ApprovalChannel`.
+//!
+//! This removes the manual-approval friction for commands a session has
+//! already established as trusted (or untrusted), while every auto-resolved
+//! decision is still recorded through `ApprovalChannel::record_auto_decision`
+//! so the audit trail (`ApprovalEvent` history/SSE replay) covers it exactly
+//! like an interactive one.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn default_timeout_seconds() -> u64 {
+    30
+}
+
+/// Rules governing whether a command needs an interactive approval.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// Glob patterns (e.g. `"git *"`) that auto-approve a matching command.
+    /// Checked after `deny_patterns` and `remembered`, so an explicit deny
+    /// or remembered rejection still wins.
+    #[serde(default)]
+    pub allow_patterns: Vec<String>,
+    /// Glob patterns that auto-reject a matching command.
+    #[serde(default)]
+    pub deny_patterns: Vec<String>,
+    /// Exact commands a user chose to "always allow"/"always deny" for the
+    /// rest of the session's lifetime, keyed by the literal command text.
+    #[serde(default)]
+    pub remembered: HashMap<String, bool>,
+    /// Approval timeout in seconds used when a tool call doesn't specify
+    /// its own; expiry resolves to the default action (deny), distinct
+    /// from an explicit rejection.
+    #[serde(default = "default_timeout_seconds")]
+    pub default_timeout_seconds: u64,
+    /// Require interactive approval for read-only SFTP tools
+    /// (`fs_read_file`/`fs_list_dir`) too, not just writes. Off by default,
+    /// since most sessions don't want a prompt for every file read.
+    #[serde(default)]
+    pub require_approval_for_reads: bool,
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            allow_patterns: Vec::new(),
+            deny_patterns: Vec::new(),
+            remembered: HashMap::new(),
+            default_timeout_seconds: default_timeout_seconds(),
+            require_approval_for_reads: false,
+        }
+    }
+}
+
+/// What the policy decides for a command, before any interactive step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyDecision {
+    AutoApproved,
+    AutoRejected,
+    RequiresApproval,
+}
+
+impl ApprovalPolicy {
+    /// Evaluate `command` against remembered decisions, then the deny
+    /// list, then the allow list, in that order of precedence.
+    pub fn evaluate(&self, command: &str) -> PolicyDecision {
+        if let Some(&approved) = self.remembered.get(command) {
+            return if approved {
+                PolicyDecision::AutoApproved
+            } else {
+                PolicyDecision::AutoRejected
+            };
+        }
+        if self.deny_patterns.iter().any(|p| glob_match(p, command)) {
+            return PolicyDecision::AutoRejected;
+        }
+        if self.allow_patterns.iter().any(|p| glob_match(p, command)) {
+            return PolicyDecision::AutoApproved;
+        }
+        PolicyDecision::RequiresApproval
+    }
+
+    /// Record a user's "always allow"/"always deny" choice for `command`,
+    /// taking precedence over the glob lists for the rest of the session.
+    pub fn remember(&mut self, command: String, approved: bool) {
+        self.remembered.insert(command, approved);
+    }
+}
+
+/// Minimal shell-style glob match: `*` matches any run of characters (including
+/// none), `?` matches exactly one. No character classes or brace expansion -
+/// allow/deny entries are simple command prefixes/suffixes (e.g. `"git *"`,
+/// `"rm -rf *"`), not full glob syntax.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let mut dp = vec![vec![false; t.len() + 1]; p.len() + 1];
+    dp[0][0] = true;
+    for i in 1..=p.len() {
+        if p[i - 1] == '*' {
+            dp[i][0] = dp[i - 1][0];
+        }
+    }
+    for i in 1..=p.len() {
+        for j in 1..=t.len() {
+            dp[i][j] = match p[i - 1] {
+                '*' => dp[i - 1][j] || dp[i][j - 1],
+                '?' => dp[i - 1][j - 1],
+                c => dp[i - 1][j - 1] && c == t[j - 1],
+            };
+        }
+    }
+    dp[p.len()][t.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("git *", "git status"));
+        assert!(glob_match("*.log", "output.log"));
+        assert!(!glob_match("git *", "rm -rf /"));
+        assert!(glob_match("ls -?", "ls -l"));
+        assert!(!glob_match("ls -?", "ls -la"));
+        assert!(glob_match("exact", "exact"));
+    }
+
+    #[test]
+    fn test_evaluate_precedence() {
+        let mut policy = ApprovalPolicy {
+            allow_patterns: vec!["git *".to_string()],
+            deny_patterns: vec!["git push *".to_string()],
+            ..ApprovalPolicy::default()
+        };
+
+        assert_eq!(policy.evaluate("git status"), PolicyDecision::AutoApproved);
+        // Deny list wins over a broader allow match.
+        assert_eq!(
+            policy.evaluate("git push origin main"),
+            PolicyDecision::AutoRejected
+        );
+        assert_eq!(
+            policy.evaluate("rm -rf /"),
+            PolicyDecision::RequiresApproval
+        );
+
+        // A remembered decision overrides both lists.
+        policy.remember("git push origin main".to_string(), true);
+        assert_eq!(
+            policy.evaluate("git push origin main"),
+            PolicyDecision::AutoApproved
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! Per-session token-bucket rate limiting for MCP `tools/call` requests.
+//!
+//! Keeps one runaway session from starving others sharing the same server
+//! by capping how often `tools/call` can be invoked per `session_uuid`.
+
+use dashmap::DashMap;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// Rate limit configuration, analogous to a per-backend `max_requests_per_second`.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitConfig {
+    /// Sustained requests per second allowed once the bucket is full.
+    pub max_requests_per_second: f64,
+    /// Maximum number of tokens the bucket can hold (burst capacity).
+    pub burst: f64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            max_requests_per_second: 5.0,
+            burst: 10.0,
+        }
+    }
+}
+
+/// A single session's token bucket.
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Result of checking a session's bucket.
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// How long the caller should wait before retrying, if not allowed.
+    pub retry_after: Duration,
+}
+
+/// Token-bucket rate limiter keyed by session UUID.
+///
+/// `initialize`/`tools/list` are exempt; only `tools/call` should consult this.
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: DashMap<Uuid, Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: DashMap::new(),
+        }
+    }
+
+    /// Attempt to consume one token for `session_uuid`, refilling first.
+    pub fn check(&self, session_uuid: Uuid) -> RateLimitDecision {
+        let now = Instant::now();
+        let mut bucket = self.buckets.entry(session_uuid).or_insert_with(|| Bucket {
+            tokens: self.config.burst,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.config.max_requests_per_second)
+            .min(self.config.burst);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            RateLimitDecision {
+                allowed: true,
+                retry_after: Duration::ZERO,
+            }
+        } else {
+            let deficit = 1.0 - bucket.tokens;
+            let wait_secs = deficit / self.config.max_requests_per_second;
+            RateLimitDecision {
+                allowed: false,
+                retry_after: Duration::from_secs_f64(wait_secs.max(0.0)),
+            }
+        }
+    }
+
+    /// Drop the bucket for a session, e.g. when its session is torn down.
+    pub fn remove(&self, session_uuid: &Uuid) {
+        self.buckets.remove(session_uuid);
+    }
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_throttle() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests_per_second: 1.0,
+            burst: 2.0,
+        });
+        let session = Uuid::new_v4();
+
+        assert!(limiter.check(session).allowed);
+        assert!(limiter.check(session).allowed);
+        let decision = limiter.check(session);
+        assert!(!decision.allowed);
+        assert!(decision.retry_after > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_independent_sessions() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_requests_per_second: 1.0,
+            burst: 1.0,
+        });
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+
+        assert!(limiter.check(a).allowed);
+        assert!(!limiter.check(a).allowed);
+        // Session b has its own bucket and is unaffected by a's usage.
+        assert!(limiter.check(b).allowed);
+    }
+}
@@ -3,50 +3,313 @@
 //! Provides SSH tool implementations with manual JSON-RPC handling.
 //! Uses rmcp 0.12.0 model types for MCP-compliant responses.
 
-use crate::mcp::approval::ApprovalChannel;
-use crate::ssh::{SshConfig, SshSession};
+use crate::forward::{ForwardDirection, ForwardProtocol, ForwardSpec};
+use crate::known_hosts::{HostKeyError, HostKeyPolicy};
+use crate::mcp::approval::{ApprovalChannel, ApprovalError, ApprovalOutcome};
+use crate::mcp::auth_challenge::{AuthChallengeChannel, AuthChallengeOutcome};
+use crate::mcp::policy::{ApprovalPolicy, PolicyDecision};
+use crate::ssh::{AuthMethod, KeyboardInteractiveHandler, SshConfig, SshFamily, SshSession};
+use futures::future::BoxFuture;
 use rmcp::model::{CallToolResult, Content, Tool};
+use russh_sftp::client::SftpSession;
+use russh_sftp::protocol::OpenFlags;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::{broadcast, Mutex, RwLock};
 use uuid::Uuid;
 
-/// Shared SSH session state for MCP tools.
-pub struct SshState {
-    pub session: Option<Arc<Mutex<SshSession>>>,
+/// Progress emitted while a chained multi-step `tools/call` is executing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub struct ToolProgressEvent {
+    pub step: usize,
+    pub total: usize,
+    pub tool: String,
+    pub status: ToolProgressStatus,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolProgressStatus {
+    Started,
+    Completed,
+}
+
+/// A chunk of output streamed from an `ssh_shell` PTY, relayed over SSE so
+/// Gemini sees interactive output incrementally instead of waiting for a
+/// single blocking read.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShellOutputEvent {
+    pub connection: String,
+    pub data: String,
+}
+
+/// One step of a server-driven multi-step tool-calling loop.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolStep {
+    pub name: String,
+    #[serde(default = "default_arguments")]
+    pub arguments: Value,
+}
+
+fn default_arguments() -> Value {
+    json!({})
+}
+
+/// Capped exponential backoff settings for reconnecting a dropped
+/// connection, configurable per `ssh_connect` call.
+#[derive(Clone, Copy)]
+pub struct ReconnectPolicy {
+    pub max_attempts: u32,
+    pub backoff_ceiling: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            backoff_ceiling: Duration::from_secs(30),
+        }
+    }
+}
+
+/// One named SSH connection held by a `SshState`.
+pub struct ConnectionEntry {
+    pub session: Arc<Mutex<SshSession>>,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    /// Detected once at connect time, copied out of the session so callers
+    /// can read it without locking (mirrors `host`/`port`/`username` above).
+    pub family: SshFamily,
     output_buffer: Arc<RwLock<Vec<String>>>,
+    /// Retained so a dropped connection can be transparently re-established
+    /// without the caller having to call `ssh_connect` again.
+    config: SshConfig,
+    reconnect_policy: ReconnectPolicy,
+    /// Updated every time a tool call resolves this connection, consulted by
+    /// `evict_idle` to find connections an abandoned multi-host workflow
+    /// left open.
+    last_activity: Arc<RwLock<Instant>>,
+}
+
+/// Idle timeout for a named connection before the background reaper closes
+/// it, reusing the 300s value `SshSession::connect` already sets as the
+/// transport's own keepalive inactivity timeout.
+pub const CONNECTION_IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Named multi-connection SSH state for MCP tools.
+///
+/// A session can hold several simultaneous SSH connections, keyed by a
+/// connection name (defaulting to `host:port`), so Gemini can fan out to
+/// multiple remote hosts - e.g. read output from one box and run it on
+/// another - without one `ssh_connect` clobbering another.
+pub struct SshState {
+    connections: HashMap<String, ConnectionEntry>,
+    /// The most recently connected name, used when a tool call omits
+    /// `connection` and there's more than one open (a single connection is
+    /// always unambiguous regardless of this).
+    last_connected: Option<String>,
+    /// Output fed by the session's legacy single-terminal SSH widget
+    /// (`Session::add_ssh_output`), which predates named connections and
+    /// isn't tied to any particular one.
+    terminal_output_buffer: Arc<RwLock<Vec<String>>>,
 }
 
 impl SshState {
     pub fn new() -> Self {
         Self {
-            session: None,
-            output_buffer: Arc::new(RwLock::new(Vec::new())),
+            connections: HashMap::new(),
+            last_connected: None,
+            terminal_output_buffer: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
-    /// Add output to the buffer.
-    pub async fn add_output(&self, output: String) {
-        let mut buffer = self.output_buffer.write().await;
-        buffer.push(output);
-        // Keep buffer manageable
-        if buffer.len() > 100 {
-            let remove_count = buffer.len() - 100;
-            buffer.drain(0..remove_count);
+    /// Register a new connection under `name`, replacing any existing
+    /// connection with that name.
+    pub fn insert_connection(
+        &mut self,
+        name: String,
+        session: SshSession,
+        config: SshConfig,
+        reconnect_policy: ReconnectPolicy,
+    ) {
+        let family = session.family();
+        self.connections.insert(
+            name.clone(),
+            ConnectionEntry {
+                session: Arc::new(Mutex::new(session)),
+                host: config.host.clone(),
+                port: config.port,
+                username: config.username.clone(),
+                family,
+                output_buffer: Arc::new(RwLock::new(Vec::new())),
+                config,
+                reconnect_policy,
+                last_activity: Arc::new(RwLock::new(Instant::now())),
+            },
+        );
+        self.last_connected = Some(name);
+    }
+
+    /// Record activity on a named connection, resetting the idle timer
+    /// `evict_idle` checks. A no-op if `name` isn't open.
+    pub async fn touch(&self, name: &str) {
+        if let Some(entry) = self.connections.get(name) {
+            *entry.last_activity.write().await = Instant::now();
+        }
+    }
+
+    /// Remove and return every named connection idle past `timeout`, for the
+    /// background reaper to close. Scoped to individual named connections
+    /// within a still-active session, unlike `spawn_session_reaper`'s
+    /// eviction of whole abandoned sessions.
+    pub async fn evict_idle(&mut self, timeout: Duration) -> Vec<(String, ConnectionEntry)> {
+        let mut idle_names = Vec::new();
+        for (name, entry) in self.connections.iter() {
+            if entry.last_activity.read().await.elapsed() >= timeout {
+                idle_names.push(name.clone());
+            }
+        }
+
+        idle_names
+            .into_iter()
+            .filter_map(|name| {
+                let entry = self.remove_connection(&name)?;
+                Some((name, entry))
+            })
+            .collect()
+    }
+
+    /// The configured reconnect policy for a named connection, if it exists.
+    pub fn reconnect_policy(&self, name: &str) -> Option<ReconnectPolicy> {
+        self.connections.get(name).map(|e| e.reconnect_policy)
+    }
+
+    /// Re-establish a named connection using its retained `SshConfig`,
+    /// replacing the dead session in place so every clone of its
+    /// `Arc<Mutex<SshSession>>` picks up the new one.
+    pub async fn reconnect(&self, name: &str) -> Result<(), String> {
+        let entry = self
+            .connections
+            .get(name)
+            .ok_or_else(|| format!("No SSH connection named \"{}\".", name))?;
+        let new_session = SshSession::connect(entry.config.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        *entry.session.lock().await = new_session;
+        Ok(())
+    }
+
+    /// Resolve `requested` (or, if omitted, the sole/most recent connection)
+    /// to a connection name and its session handle.
+    fn resolve(&self, requested: Option<&str>) -> Result<(String, Arc<Mutex<SshSession>>), String> {
+        if self.connections.is_empty() {
+            return Err("SSH not connected. Call ssh_connect first.".to_string());
+        }
+
+        let name = match requested {
+            Some(name) => name.to_string(),
+            None if self.connections.len() == 1 => {
+                self.connections.keys().next().cloned().unwrap()
+            }
+            None => self.last_connected.clone().ok_or_else(|| {
+                "Multiple SSH connections are open; specify `connection` (see ssh_list_connections)."
+                    .to_string()
+            })?,
+        };
+
+        match self.connections.get(&name) {
+            Some(entry) => Ok((name, entry.session.clone())),
+            None => Err(format!(
+                "No SSH connection named \"{}\". See ssh_list_connections.",
+                name
+            )),
+        }
+    }
+
+    /// Remove and return a named connection, if one exists.
+    pub fn remove_connection(&mut self, name: &str) -> Option<ConnectionEntry> {
+        if self.last_connected.as_deref() == Some(name) {
+            self.last_connected = None;
+        }
+        self.connections.remove(name)
+    }
+
+    /// List connection names with their host/port/username/family.
+    pub fn list_connections(&self) -> Vec<(String, String, u16, String, SshFamily)> {
+        self.connections
+            .iter()
+            .map(|(name, entry)| {
+                (
+                    name.clone(),
+                    entry.host.clone(),
+                    entry.port,
+                    entry.username.clone(),
+                    entry.family,
+                )
+            })
+            .collect()
+    }
+
+    /// Add output to the named connection's buffer.
+    async fn add_connection_output(&self, name: &str, output: String) {
+        if let Some(entry) = self.connections.get(name) {
+            push_bounded(&entry.output_buffer, output).await;
+        }
+    }
+
+    /// Get recent output lines from the named connection's buffer.
+    async fn get_connection_output(&self, name: &str, lines: usize) -> Vec<String> {
+        match self.connections.get(name) {
+            Some(entry) => recent(&entry.output_buffer, lines).await,
+            None => Vec::new(),
         }
     }
 
-    /// Get recent output lines.
+    /// Add output from the legacy single-terminal SSH widget.
+    pub async fn add_output(&self, output: String) {
+        push_bounded(&self.terminal_output_buffer, output).await;
+    }
+
+    /// Get recent output from the legacy single-terminal SSH widget.
     pub async fn get_recent_output(&self, lines: usize) -> Vec<String> {
-        let buffer = self.output_buffer.read().await;
-        let start = buffer.len().saturating_sub(lines);
-        buffer[start..].to_vec()
+        recent(&self.terminal_output_buffer, lines).await
+    }
+}
+
+/// Push `output` onto `buffer`, trimming it to the most recent 100 entries.
+async fn push_bounded(buffer: &Arc<RwLock<Vec<String>>>, output: String) {
+    let mut buffer = buffer.write().await;
+    buffer.push(output);
+    if buffer.len() > 100 {
+        let remove_count = buffer.len() - 100;
+        buffer.drain(0..remove_count);
     }
 }
 
+/// The most recent `lines` entries of `buffer`.
+async fn recent(buffer: &Arc<RwLock<Vec<String>>>, lines: usize) -> Vec<String> {
+    let buffer = buffer.read().await;
+    let start = buffer.len().saturating_sub(lines);
+    buffer[start..].to_vec()
+}
+
+/// A small random jitter (0-200ms) to add to a reconnect backoff delay, so
+/// concurrently failing connections don't all retry in lockstep. Built from
+/// `RandomState`'s OS-seeded hasher rather than pulling in a `rand`
+/// dependency for one call site.
+fn jitter() -> Duration {
+    use std::hash::{BuildHasher, Hasher, RandomState};
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_millis(random % 200)
+}
+
 // ============================================================================
 // Tool Parameter Types (with JsonSchema for automatic schema generation)
 // ============================================================================
@@ -61,12 +324,65 @@ pub struct SshConnectParams {
     pub port: u16,
     /// Username for authentication.
     pub username: String,
-    /// Password for authentication (optional if using key).
+    /// Password for authentication (optional if using a key or the agent).
     #[serde(default)]
     pub password: Option<String>,
-    /// Private key for authentication (optional if using password).
+    /// Private key material, inline (optional if using `identity_file` or
+    /// the agent).
     #[serde(default)]
     pub private_key: Option<String>,
+    /// Path to a private key file to read `private_key` from, so it never
+    /// has to be pasted into the conversation.
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    /// Passphrase to decrypt `private_key`/`identity_file`, if encrypted.
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Authenticate against the user's running ssh-agent (`$SSH_AUTH_SOCK`)
+    /// instead of `private_key`/`identity_file`/`password`, so key material
+    /// never crosses this tool call.
+    #[serde(default)]
+    pub use_agent: bool,
+    /// Agent socket path; defaults to `$SSH_AUTH_SOCK` when omitted.
+    #[serde(default)]
+    pub agent_socket: Option<String>,
+    /// Forward the agent connection to the remote (only meaningful with
+    /// `use_agent`).
+    #[serde(default)]
+    pub forward_agent: bool,
+    /// Name to register this connection under, for later `connection`
+    /// parameters on `ssh_execute`/`ssh_read_output`/`ssh_disconnect`.
+    /// Defaults to `host:port`. Connecting again under a name already in
+    /// use replaces the earlier connection.
+    #[serde(default)]
+    pub connection_name: Option<String>,
+    /// Maximum number of automatic reconnect attempts after the transport
+    /// drops, before an `ssh_execute` call gives up and surfaces the error
+    /// (default: 5).
+    #[serde(default)]
+    pub max_reconnect_attempts: Option<u32>,
+    /// Ceiling, in milliseconds, on the capped exponential backoff between
+    /// reconnect attempts (default: 30000).
+    #[serde(default)]
+    pub reconnect_backoff_ceiling_ms: Option<u64>,
+    /// How to handle the server's host key: `strict` (reject an unknown
+    /// host), `accept_new` (trust-on-first-use; default) or `accept_all`
+    /// (trust any key, e.g. for disposable lab boxes). If the key has
+    /// changed since a previous connection, the user is prompted to approve
+    /// trusting it through the same approval flow as `ssh_execute`.
+    #[serde(default)]
+    pub host_key_policy: HostKeyPolicy,
+    /// Ordered authentication methods to try, each attempted against the
+    /// same connection so a server requiring more than one (e.g. a public
+    /// key, then a keyboard-interactive OTP prompt) chains through them -
+    /// `["public_key", "keyboard_interactive"]` for "key THEN OTP". Defaults
+    /// to inferring a single method from `use_agent`/`password`/
+    /// `private_key`/`identity_file` when omitted. Including
+    /// `keyboard_interactive` surfaces each server prompt as an auth
+    /// challenge event for the frontend to answer, the same way a command
+    /// approval is surfaced.
+    #[serde(default)]
+    pub auth_methods: Vec<AuthMethod>,
 }
 
 fn default_port() -> u16 {
@@ -78,16 +394,18 @@ fn default_port() -> u16 {
 pub struct SshExecuteParams {
     /// The command to execute on the remote server.
     pub command: String,
-    /// Timeout in seconds for user approval (default: 30).
-    #[serde(default = "default_timeout")]
-    pub timeout_seconds: u64,
+    /// Timeout in seconds for user approval. Falls back to the session's
+    /// `ApprovalPolicy::default_timeout_seconds` when omitted.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
     /// Whether to wait for command output before returning (default: true).
     #[serde(default = "default_wait")]
     pub wait_for_output: bool,
-}
-
-fn default_timeout() -> u64 {
-    30
+    /// Which connection to run on. Defaults to the only open connection;
+    /// with several open, to the most recently connected one (an error if
+    /// that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
 }
 
 fn default_wait() -> bool {
@@ -100,16 +418,264 @@ pub struct SshReadOutputParams {
     /// Number of recent output lines to retrieve (default: 50).
     #[serde(default = "default_lines")]
     pub lines: usize,
+    /// Which connection to read from. Defaults to the only open connection;
+    /// with several open, to the most recently connected one (an error if
+    /// that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
 }
 
 fn default_lines() -> usize {
     50
 }
 
+/// Parameters for ssh_list_connections tool (no parameters).
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshListConnectionsParams {}
+
+/// Parameters for ssh_disconnect tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshDisconnectParams {
+    /// Name of the connection to close, as registered by `ssh_connect`
+    /// (or its default `host:port`).
+    pub connection: String,
+}
+
+/// One entry returned by `ssh_list_connections`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SshConnectionInfo {
+    pub connection: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub family: SshFamily,
+}
+
+/// Parameters for ssh_shell tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshShellParams {
+    /// Raw input to write to the PTY, exactly as typed - include a trailing
+    /// `\n` to submit a line, or omit one to feed a partial line (e.g. to a
+    /// pager or REPL prompt).
+    pub input: String,
+    /// Resize the PTY to this many columns before writing `input`. Applied
+    /// only when `rows` is given too.
+    #[serde(default)]
+    pub cols: Option<u32>,
+    /// Resize the PTY to this many rows before writing `input`. Applied only
+    /// when `cols` is given too.
+    #[serde(default)]
+    pub rows: Option<u32>,
+    /// Timeout in seconds for user approval. Falls back to the session's
+    /// `ApprovalPolicy::default_timeout_seconds` when omitted.
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// Which connection to write to. Defaults to the only open connection;
+    /// with several open, to the most recently connected one (an error if
+    /// that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for ssh_resize tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshResizeParams {
+    /// New terminal width, in columns.
+    pub cols: u32,
+    /// New terminal height, in rows.
+    pub rows: u32,
+    /// Which connection to resize. Defaults to the only open connection;
+    /// with several open, to the most recently connected one (an error if
+    /// that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for ssh_send_signal tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshSendSignalParams {
+    /// Signal to deliver via its terminal control character: `SIGINT`
+    /// (Ctrl-C), `SIGQUIT` (Ctrl-\) or `SIGTSTP` (Ctrl-Z).
+    pub signal: String,
+    /// Which connection to signal. Defaults to the only open connection;
+    /// with several open, to the most recently connected one (an error if
+    /// that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for ssh_forward_local tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshForwardLocalParams {
+    /// Local address to bind the forward's listener on.
+    #[serde(default = "default_forward_bind_addr")]
+    pub bind_addr: String,
+    /// Local port to bind the listener on.
+    pub bind_port: u16,
+    /// Host to forward to, resolved from the SSH server's side.
+    pub target_host: String,
+    /// Port to forward to, on `target_host`.
+    pub target_port: u16,
+    /// TCP or UDP (default: tcp).
+    #[serde(default)]
+    pub protocol: ForwardProtocol,
+    /// Which connection to forward through. Defaults to the only open
+    /// connection; with several open, to the most recently connected one
+    /// (an error if that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for ssh_forward_remote tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshForwardRemoteParams {
+    /// Address the SSH server should bind its listener on (e.g. `0.0.0.0`
+    /// to listen on all of the server's interfaces, `127.0.0.1` for
+    /// server-local connections only).
+    #[serde(default = "default_forward_bind_addr")]
+    pub bind_addr: String,
+    /// Port the SSH server should bind its listener on.
+    pub bind_port: u16,
+    /// Host reachable from this machine to forward incoming connections to.
+    #[serde(default = "default_forward_bind_addr")]
+    pub target_host: String,
+    /// Port to forward to, on `target_host`.
+    pub target_port: u16,
+    /// Which connection to forward through. Defaults to the only open
+    /// connection; with several open, to the most recently connected one
+    /// (an error if that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+fn default_forward_bind_addr() -> String {
+    "127.0.0.1".to_string()
+}
+
+/// Parameters for ssh_cancel_forward tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct SshCancelForwardParams {
+    /// Forward id, as returned by `ssh_forward_local`/`ssh_forward_remote`.
+    pub id: String,
+    /// Which connection the forward is on. Defaults to the only open
+    /// connection; with several open, to the most recently connected one
+    /// (an error if that one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for fs_read_file tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FsReadFileParams {
+    /// Path to the file on the remote server, relative to the login shell's
+    /// working directory unless absolute.
+    pub path: String,
+    /// Which connection to use. Defaults to the only open connection; with
+    /// several open, to the most recently connected one (an error if that
+    /// one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for fs_write_file tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FsWriteFileParams {
+    /// Path to the file on the remote server.
+    pub path: String,
+    /// Content to write.
+    pub content: String,
+    /// Append to the file instead of overwriting it (default: false).
+    #[serde(default)]
+    pub append: bool,
+    /// Which connection to use. Defaults to the only open connection; with
+    /// several open, to the most recently connected one (an error if that
+    /// one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for fs_list_dir tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FsListDirParams {
+    /// Path to the directory on the remote server.
+    pub path: String,
+    /// Which connection to use. Defaults to the only open connection; with
+    /// several open, to the most recently connected one (an error if that
+    /// one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for fs_rename tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FsRenameParams {
+    /// Existing path on the remote server.
+    pub from: String,
+    /// Destination path on the remote server.
+    pub to: String,
+    /// Which connection to use. Defaults to the only open connection; with
+    /// several open, to the most recently connected one (an error if that
+    /// one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for fs_delete tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FsDeleteParams {
+    /// Path to the file or directory on the remote server.
+    pub path: String,
+    /// Recursively delete a directory and everything under it
+    /// (default: false; required to delete a non-empty directory).
+    #[serde(default)]
+    pub recursive: bool,
+    /// Which connection to use. Defaults to the only open connection; with
+    /// several open, to the most recently connected one (an error if that
+    /// one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// Parameters for fs_mkdir tool.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct FsMkdirParams {
+    /// Path of the directory to create on the remote server.
+    pub path: String,
+    /// Create missing parent directories, like `mkdir -p` (default: false).
+    #[serde(default)]
+    pub recursive: bool,
+    /// Which connection to use. Defaults to the only open connection; with
+    /// several open, to the most recently connected one (an error if that
+    /// one has since been disconnected).
+    #[serde(default)]
+    pub connection: Option<String>,
+}
+
+/// One entry returned by `fs_list_dir`.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsDirEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub mtime: u64,
+    pub mode: u32,
+}
+
 // ============================================================================
 // Helper functions
 // ============================================================================
 
+/// The terminal control character that delivers `signal` to a PTY's
+/// foreground process, for `ssh_send_signal`.
+fn signal_to_control_byte(signal: &str) -> Option<u8> {
+    match signal.to_ascii_uppercase().as_str() {
+        "SIGINT" => Some(0x03),  // Ctrl-C
+        "SIGQUIT" => Some(0x1c), // Ctrl-\
+        "SIGTSTP" => Some(0x1a), // Ctrl-Z
+        _ => None,
+    }
+}
+
 /// Convert a schemars schema to the Arc<Map<String, Value>> format expected by rmcp.
 fn schema_to_arc_map<T: JsonSchema>() -> Arc<Map<String, Value>> {
     let schema = schemars::schema_for!(T);
@@ -130,16 +696,91 @@ pub struct McpSshService {
     pub session_id: Uuid,
     ssh_state: Arc<RwLock<SshState>>,
     pub approval_channel: Arc<ApprovalChannel>,
+    /// Keyboard-interactive (PAM/MFA) auth prompt rounds raised by
+    /// `ssh_connect`, surfaced to the frontend the same way `approval_channel`
+    /// surfaces command approvals. Independent of `approval_channel` since a
+    /// challenge only exists for the duration of one connect call.
+    pub auth_challenge_channel: Arc<AuthChallengeChannel>,
+    /// Allow/deny lists and remembered decisions consulted before a
+    /// side-effecting tool blocks on interactive approval. Shared with the
+    /// owning `Session` so the `/mcp/:session_id/policy` REST endpoints and
+    /// a "remember this decision" choice from the command WebSocket can
+    /// edit the same policy tool calls read.
+    pub policy: Arc<RwLock<ApprovalPolicy>>,
+    /// Progress events for chained/multi-step `tools/call` loops, relayed over SSE.
+    progress_tx: broadcast::Sender<ToolProgressEvent>,
+    /// Output chunks streamed from an in-progress `ssh_shell` PTY, relayed over SSE.
+    shell_output_tx: broadcast::Sender<ShellOutputEvent>,
+    /// Maximum number of steps a single chained `tools/call` may execute.
+    max_chain_steps: usize,
+    /// Shared with the owning `Session` so a `tools/call` counts as activity
+    /// for the session-TTL reaper, the same as WebSocket traffic does.
+    last_active: Arc<RwLock<Instant>>,
 }
 
 impl McpSshService {
     /// Create a new MCP SSH service.
-    pub fn new(session_id: Uuid, approval_channel: Arc<ApprovalChannel>) -> Self {
+    pub fn new(
+        session_id: Uuid,
+        approval_channel: Arc<ApprovalChannel>,
+        policy: Arc<RwLock<ApprovalPolicy>>,
+        last_active: Arc<RwLock<Instant>>,
+    ) -> Self {
+        let (progress_tx, _) = broadcast::channel(64);
+        let (shell_output_tx, _) = broadcast::channel(64);
         Self {
             session_id,
             ssh_state: Arc::new(RwLock::new(SshState::new())),
             approval_channel,
+            auth_challenge_channel: Arc::new(AuthChallengeChannel::new()),
+            policy,
+            progress_tx,
+            shell_output_tx,
+            max_chain_steps: 10,
+            last_active,
+        }
+    }
+
+    /// Subscribe to progress events for chained tool calls.
+    pub fn subscribe_progress(&self) -> broadcast::Receiver<ToolProgressEvent> {
+        self.progress_tx.subscribe()
+    }
+
+    /// Subscribe to streamed `ssh_shell` output chunks.
+    pub fn subscribe_shell_output(&self) -> broadcast::Receiver<ShellOutputEvent> {
+        self.shell_output_tx.subscribe()
+    }
+
+    /// Run a bounded chain of tool calls, emitting progress over
+    /// `subscribe_progress` as each step runs. Every step always runs
+    /// through `call_tool` - chains never cache results across steps, since
+    /// that would skip the `authorize`/`authorize_read` policy check a
+    /// repeated step must go through just as much as its first occurrence.
+    pub async fn call_tool_chain(&self, steps: Vec<ToolStep>) -> Vec<CallToolResult> {
+        let total = steps.len().min(self.max_chain_steps);
+        let mut results = Vec::with_capacity(total);
+
+        for (index, step) in steps.into_iter().take(self.max_chain_steps).enumerate() {
+            let _ = self.progress_tx.send(ToolProgressEvent {
+                step: index + 1,
+                total,
+                tool: step.name.clone(),
+                status: ToolProgressStatus::Started,
+            });
+
+            let result = self.call_tool(&step.name, step.arguments).await;
+
+            let _ = self.progress_tx.send(ToolProgressEvent {
+                step: index + 1,
+                total,
+                tool: step.name.clone(),
+                status: ToolProgressStatus::Completed,
+            });
+
+            results.push(result);
         }
+
+        results
     }
 
     /// Get server info for MCP initialization.
@@ -155,7 +796,11 @@ impl McpSshService {
         vec![
             Tool {
                 name: "ssh_connect".into(),
-                description: Some("Connect to a remote SSH server. Must be called before executing commands.".into()),
+                description: Some("Connect to a remote SSH server, registering it under `connection_name` \
+                    (default host:port) so several hosts can be connected at once. Must be called \
+                    before executing commands on a host. `auth_methods` can chain more than one \
+                    method (e.g. `[\"public_key\", \"keyboard_interactive\"]`) for servers requiring \
+                    a PAM/MFA challenge on top of a key or password.".into()),
                 input_schema: schema_to_arc_map::<SshConnectParams>(),
                 annotations: None,
                 output_schema: None,
@@ -165,7 +810,9 @@ impl McpSshService {
             },
             Tool {
                 name: "ssh_execute".into(),
-                description: Some("Execute a command on the connected SSH server. Requires user approval.".into()),
+                description: Some("Execute a command on a connected SSH server (see `connection`). \
+                    For pagers, REPLs, sudo prompts or anything else that needs back-and-forth \
+                    interaction, use `ssh_shell` instead. Requires user approval.".into()),
                 input_schema: schema_to_arc_map::<SshExecuteParams>(),
                 annotations: None,
                 output_schema: None,
@@ -175,7 +822,7 @@ impl McpSshService {
             },
             Tool {
                 name: "ssh_read_output".into(),
-                description: Some("Read recent output from the SSH terminal session.".into()),
+                description: Some("Read recent output from a connected SSH server (see `connection`).".into()),
                 input_schema: schema_to_arc_map::<SshReadOutputParams>(),
                 annotations: None,
                 output_schema: None,
@@ -183,15 +830,208 @@ impl McpSshService {
                 icons: None,
                 title: None,
             },
+            Tool {
+                name: "ssh_list_connections".into(),
+                description: Some("List the SSH connections currently open in this session, with the \
+                    name each was registered under.".into()),
+                input_schema: schema_to_arc_map::<SshListConnectionsParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: None,
+            },
+            Tool {
+                name: "ssh_disconnect".into(),
+                description: Some("Close a named SSH connection. Requires user approval.".into()),
+                input_schema: schema_to_arc_map::<SshDisconnectParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("SSH Disconnect (requires approval)".into()),
+            },
+            Tool {
+                name: "ssh_shell".into(),
+                description: Some("Write raw input to an interactive PTY on a connected SSH server \
+                    (see `connection`) and stream its output back incrementally as `ssh_shell_output` \
+                    events on the session's SSE endpoint, instead of a single blocking read. Use this \
+                    for pagers, REPLs, sudo prompts and long-running commands that `ssh_execute` can't \
+                    handle well. Requires user approval.".into()),
+                input_schema: schema_to_arc_map::<SshShellParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("SSH Shell (requires approval)".into()),
+            },
+            Tool {
+                name: "ssh_resize".into(),
+                description: Some("Resize the PTY window on a connected SSH server (see `connection`), \
+                    so full-screen programs reflow instead of drawing to a stale size.".into()),
+                input_schema: schema_to_arc_map::<SshResizeParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: None,
+            },
+            Tool {
+                name: "ssh_send_signal".into(),
+                description: Some("Send a signal (SIGINT/SIGQUIT/SIGTSTP) to the foreground process on \
+                    a connected SSH server's PTY (see `connection`), e.g. Ctrl-C to interrupt a stuck \
+                    command. Requires user approval.".into()),
+                input_schema: schema_to_arc_map::<SshSendSignalParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("SSH Send Signal (requires approval)".into()),
+            },
+            Tool {
+                name: "ssh_forward_local".into(),
+                description: Some("Open a local port forward on a connected SSH server (see \
+                    `connection`): bind a local listener and tunnel each accepted connection to a \
+                    host/port reachable from the remote end, as OpenSSH's `-L` does. Returns a \
+                    forward id for `ssh_cancel_forward`. Requires user approval.".into()),
+                input_schema: schema_to_arc_map::<SshForwardLocalParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("SSH Forward Local Port (requires approval)".into()),
+            },
+            Tool {
+                name: "ssh_forward_remote".into(),
+                description: Some("Open a remote port forward on a connected SSH server (see \
+                    `connection`): ask the server to bind a listener and tunnel each accepted \
+                    connection back to a host/port reachable from this machine, as OpenSSH's `-R` \
+                    does. Returns a forward id for `ssh_cancel_forward`. Requires user approval.".into()),
+                input_schema: schema_to_arc_map::<SshForwardRemoteParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("SSH Forward Remote Port (requires approval)".into()),
+            },
+            Tool {
+                name: "ssh_cancel_forward".into(),
+                description: Some("Close a port forward previously opened with `ssh_forward_local` \
+                    or `ssh_forward_remote`, by the id either returned. Requires user approval.".into()),
+                input_schema: schema_to_arc_map::<SshCancelForwardParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("SSH Cancel Forward (requires approval)".into()),
+            },
+            Tool {
+                name: "fs_read_file".into(),
+                description: Some(
+                    "Read a file on the connected SSH server over SFTP.".into(),
+                ),
+                input_schema: schema_to_arc_map::<FsReadFileParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: None,
+            },
+            Tool {
+                name: "fs_write_file".into(),
+                description: Some(
+                    "Write (or append to) a file on the connected SSH server over SFTP. \
+                    Requires user approval."
+                        .into(),
+                ),
+                input_schema: schema_to_arc_map::<FsWriteFileParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("Write File (requires approval)".into()),
+            },
+            Tool {
+                name: "fs_list_dir".into(),
+                description: Some(
+                    "List a directory on the connected SSH server over SFTP, returning \
+                    name/is_dir/size/mtime/mode for each entry."
+                        .into(),
+                ),
+                input_schema: schema_to_arc_map::<FsListDirParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: None,
+            },
+            Tool {
+                name: "fs_rename".into(),
+                description: Some(
+                    "Rename or move a file/directory on the connected SSH server over SFTP. \
+                    Requires user approval."
+                        .into(),
+                ),
+                input_schema: schema_to_arc_map::<FsRenameParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("Rename (requires approval)".into()),
+            },
+            Tool {
+                name: "fs_delete".into(),
+                description: Some(
+                    "Delete a file, or a directory (with recursive: true), on the connected \
+                    SSH server over SFTP. Requires user approval."
+                        .into(),
+                ),
+                input_schema: schema_to_arc_map::<FsDeleteParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("Delete (requires approval)".into()),
+            },
+            Tool {
+                name: "fs_mkdir".into(),
+                description: Some(
+                    "Create a directory on the connected SSH server over SFTP. \
+                    Requires user approval."
+                        .into(),
+                ),
+                input_schema: schema_to_arc_map::<FsMkdirParams>(),
+                annotations: None,
+                output_schema: None,
+                meta: None,
+                icons: None,
+                title: Some("Create Directory (requires approval)".into()),
+            },
         ]
     }
 
     /// Call a tool by name with the given arguments.
     pub async fn call_tool(&self, name: &str, arguments: Value) -> CallToolResult {
+        *self.last_active.write().await = Instant::now();
+
         match name {
             "ssh_connect" => self.tool_ssh_connect(arguments).await,
             "ssh_execute" => self.tool_ssh_execute(arguments).await,
+            "ssh_list_connections" => self.tool_ssh_list_connections().await,
+            "ssh_disconnect" => self.tool_ssh_disconnect(arguments).await,
             "ssh_read_output" => self.tool_ssh_read_output(arguments).await,
+            "ssh_shell" => self.tool_ssh_shell(arguments).await,
+            "ssh_resize" => self.tool_ssh_resize(arguments).await,
+            "ssh_send_signal" => self.tool_ssh_send_signal(arguments).await,
+            "ssh_forward_local" => self.tool_ssh_forward_local(arguments).await,
+            "ssh_forward_remote" => self.tool_ssh_forward_remote(arguments).await,
+            "ssh_cancel_forward" => self.tool_ssh_cancel_forward(arguments).await,
+            "fs_read_file" => self.tool_fs_read_file(arguments).await,
+            "fs_write_file" => self.tool_fs_write_file(arguments).await,
+            "fs_list_dir" => self.tool_fs_list_dir(arguments).await,
+            "fs_rename" => self.tool_fs_rename(arguments).await,
+            "fs_delete" => self.tool_fs_delete(arguments).await,
+            "fs_mkdir" => self.tool_fs_mkdir(arguments).await,
             _ => CallToolResult::error(vec![Content::text(format!(
                 "Unknown tool: {}",
                 name
@@ -199,7 +1039,128 @@ impl McpSshService {
         }
     }
 
-    /// Connect to a remote SSH server.
+    /// Consult the approval policy before a side-effecting tool proceeds,
+    /// and fall back to interactive approval for whatever it leaves
+    /// undecided. `action` is both the human-readable label shown to the
+    /// approver and the string evaluated against allow/deny patterns - for
+    /// `ssh_execute` this is the shell command; for the `fs_*` tools it's a
+    /// descriptive label like `"fs_delete /etc/hosts"`. `timeout_override`
+    /// lets callers that take their own `timeout_seconds` parameter pass it
+    /// through; other callers can pass `None` to use the policy's default.
+    /// `connection` is the named SSH connection the action targets, if any,
+    /// carried on the emitted `ApprovalEvent` so a multi-connection frontend
+    /// can show which host a pending approval belongs to.
+    async fn authorize(
+        &self,
+        action: String,
+        timeout_override: Option<u64>,
+        connection: Option<String>,
+    ) -> Result<(), CallToolResult> {
+        let policy_decision = self.policy.read().await.evaluate(&action);
+        let outcome = match policy_decision {
+            PolicyDecision::AutoApproved => {
+                self.approval_channel
+                    .record_auto_decision(action.clone(), true, connection)
+                    .await;
+                ApprovalOutcome::Approved
+            }
+            PolicyDecision::AutoRejected => {
+                self.approval_channel
+                    .record_auto_decision(action.clone(), false, connection)
+                    .await;
+                return Err(CallToolResult::error(vec![Content::text(
+                    "Rejected by approval policy (deny-list or remembered decision).",
+                )]));
+            }
+            PolicyDecision::RequiresApproval => {
+                // Request user approval. A timeout resolves as
+                // `ApprovalOutcome::TimedOut` below, reported distinctly
+                // from an explicit denial or cancellation.
+                let timeout_seconds = timeout_override
+                    .unwrap_or(self.policy.read().await.default_timeout_seconds);
+                let timeout = Duration::from_secs(timeout_seconds);
+                match self
+                    .approval_channel
+                    .wait_for_approval(action.clone(), timeout, connection)
+                    .await
+                {
+                    Ok(outcome) => outcome,
+                    Err(ApprovalError::ChannelClosed) => {
+                        return Err(CallToolResult::error(vec![Content::text(
+                            "Approval channel closed unexpectedly.",
+                        )]));
+                    }
+                }
+            }
+        };
+
+        match outcome {
+            ApprovalOutcome::Approved => Ok(()),
+            ApprovalOutcome::Denied { reason: Some(reason) } => {
+                Err(CallToolResult::error(vec![Content::text(format!(
+                    "Rejected by user: {reason}"
+                ))]))
+            }
+            ApprovalOutcome::Denied { reason: None } => Err(CallToolResult::error(vec![
+                Content::text("Rejected by user."),
+            ])),
+            ApprovalOutcome::Canceled => Err(CallToolResult::error(vec![Content::text(
+                "Approval request was canceled.",
+            )])),
+            ApprovalOutcome::TimedOut => Err(CallToolResult::error(vec![Content::text(
+                "Approval timed out - action not performed.",
+            )])),
+        }
+    }
+
+    /// Consult `require_approval_for_reads` before a read-only SFTP tool
+    /// (`fs_read_file`/`fs_list_dir`) proceeds - a no-op unless the session's
+    /// policy opted into gating reads too, since most sessions don't want an
+    /// interactive prompt for every file read.
+    async fn authorize_read(
+        &self,
+        action: String,
+        connection: Option<String>,
+    ) -> Result<(), CallToolResult> {
+        if !self.policy.read().await.require_approval_for_reads {
+            return Ok(());
+        }
+        self.authorize(action, None, connection).await
+    }
+
+    /// Resolve `connection` (or the sole/most recent connection, if
+    /// omitted) to its session handle, for tools that act on a single
+    /// named connection.
+    async fn resolve_connection(
+        &self,
+        connection: Option<&str>,
+    ) -> Result<(String, Arc<Mutex<SshSession>>), CallToolResult> {
+        let resolved = self
+            .ssh_state
+            .read()
+            .await
+            .resolve(connection)
+            .map_err(|message| CallToolResult::error(vec![Content::text(message)]))?;
+        self.ssh_state.read().await.touch(&resolved.0).await;
+        Ok(resolved)
+    }
+
+    /// Open a fresh SFTP channel on an already-resolved connection, for the
+    /// `fs_*` tools - they resolve the connection themselves (via
+    /// `resolve_connection`) so its name is available for the approval
+    /// prompt before the SFTP channel is opened.
+    async fn open_sftp(&self, ssh: &Arc<Mutex<SshSession>>) -> Result<SftpSession, CallToolResult> {
+        let ssh_guard = ssh.lock().await;
+        ssh_guard.open_sftp().await.map_err(|e| {
+            CallToolResult::error(vec![Content::text(format!(
+                "Failed to open SFTP session: {}",
+                e
+            ))])
+        })
+    }
+
+    /// Connect to a remote SSH server, registering it under
+    /// `connection_name` (default `host:port`).
     async fn tool_ssh_connect(&self, arguments: Value) -> CallToolResult {
         let params: SshConnectParams = match serde_json::from_value(arguments) {
             Ok(p) => p,
@@ -211,32 +1172,145 @@ impl McpSshService {
             }
         };
 
+        let private_key = if let Some(path) = &params.identity_file {
+            match tokio::fs::read_to_string(path).await {
+                Ok(key) => Some(key),
+                Err(e) => {
+                    return CallToolResult::error(vec![Content::text(format!(
+                        "Failed to read identity_file {}: {}",
+                        path, e
+                    ))]);
+                }
+            }
+        } else {
+            params.private_key.clone()
+        };
+
+        let keyboard_interactive: Option<KeyboardInteractiveHandler> = if params
+            .auth_methods
+            .contains(&AuthMethod::KeyboardInteractive)
+        {
+            let auth_challenge_channel = self.auth_challenge_channel.clone();
+            let challenge_timeout =
+                Duration::from_secs(self.policy.read().await.default_timeout_seconds);
+            Some(Arc::new(move |name, instructions, prompts| {
+                let auth_challenge_channel = auth_challenge_channel.clone();
+                Box::pin(async move {
+                    match auth_challenge_channel
+                        .request_answers(name, instructions, prompts, challenge_timeout)
+                        .await
+                    {
+                        AuthChallengeOutcome::Answered(answers) => Some(answers),
+                        AuthChallengeOutcome::TimedOut => None,
+                    }
+                })
+            }))
+        } else {
+            None
+        };
+
         let config = SshConfig {
             host: params.host.clone(),
             port: params.port,
             username: params.username.clone(),
             password: params.password.clone(),
-            private_key: params.private_key.clone(),
+            private_key,
+            passphrase: params.passphrase.clone(),
+            use_agent: params.use_agent,
+            agent_socket: params.agent_socket.clone(),
+            forward_agent: params.forward_agent,
+            term: "xterm-256color".to_string(),
+            cols: 80,
+            rows: 24,
+            host_key_policy: params.host_key_policy,
+            known_hosts_path: None,
+            auth_methods: params.auth_methods.clone(),
+            keyboard_interactive,
+        };
+
+        let connection_name = params
+            .connection_name
+            .clone()
+            .unwrap_or_else(|| format!("{}:{}", params.host, params.port));
+
+        let reconnect_policy = ReconnectPolicy {
+            max_attempts: params.max_reconnect_attempts.unwrap_or(5),
+            backoff_ceiling: Duration::from_millis(
+                params.reconnect_backoff_ceiling_ms.unwrap_or(30_000),
+            ),
         };
 
-        match SshSession::connect(config).await {
+        match SshSession::connect(config.clone()).await {
             Ok(session) => {
+                let family = session.family();
                 let mut state = self.ssh_state.write().await;
-                state.session = Some(Arc::new(Mutex::new(session)));
+                state.insert_connection(connection_name.clone(), session, config, reconnect_policy);
 
                 CallToolResult::success(vec![Content::text(format!(
-                    "Successfully connected to {}@{}:{}",
-                    params.username, params.host, params.port
+                    "Successfully connected to {}@{}:{} as connection \"{}\" ({} host)",
+                    params.username, params.host, params.port, connection_name, family
                 ))])
             }
-            Err(e) => CallToolResult::error(vec![Content::text(format!(
-                "Failed to connect: {}",
-                e
-            ))]),
+            Err(e) => {
+                // A changed host key gets a distinct, interactive path: ask
+                // the user to approve trusting the new key (through the same
+                // `ApprovalChannel` that guards `ssh_execute`) rather than
+                // either silently trusting it or failing with no recourse.
+                if let Some(HostKeyError::Mismatch {
+                    host,
+                    port,
+                    expected,
+                    actual,
+                }) = e.downcast_ref::<HostKeyError>()
+                {
+                    let prompt = format!(
+                        "[{}] Host key for {}:{} has changed (was {}, now {}). Trust the new key and connect?",
+                        connection_name, host, port, expected, actual
+                    );
+                    if let Err(result) = self
+                        .authorize(prompt, None, Some(connection_name.clone()))
+                        .await
+                    {
+                        return result;
+                    }
+
+                    // Only the retry connect itself trusts the new key
+                    // unconditionally; the config persisted for future
+                    // automatic reconnects (`SshState::reconnect`) keeps the
+                    // original `host_key_policy` so a *later* key change
+                    // still requires approval instead of TOFU being
+                    // silently disabled for the rest of the connection's life.
+                    let mut retry_config = config.clone();
+                    retry_config.host_key_policy = HostKeyPolicy::AcceptAll;
+                    return match SshSession::connect(retry_config).await {
+                        Ok(session) => {
+                            let family = session.family();
+                            let mut state = self.ssh_state.write().await;
+                            state.insert_connection(
+                                connection_name.clone(),
+                                session,
+                                config,
+                                reconnect_policy,
+                            );
+
+                            CallToolResult::success(vec![Content::text(format!(
+                                "Successfully connected to {}@{}:{} as connection \"{}\" ({} host, host key updated after approval)",
+                                params.username, params.host, params.port, connection_name, family
+                            ))])
+                        }
+                        Err(e) => CallToolResult::error(vec![Content::text(format!(
+                            "Failed to connect after accepting new host key: {}",
+                            e
+                        ))]),
+                    };
+                }
+
+                CallToolResult::error(vec![Content::text(format!("Failed to connect: {}", e))])
+            }
         }
     }
 
-    /// Execute a command on the connected SSH server.
+    /// Execute a command on a connected SSH server (see `connection`).
     async fn tool_ssh_execute(&self, arguments: Value) -> CallToolResult {
         let params: SshExecuteParams = match serde_json::from_value(arguments) {
             Ok(p) => p,
@@ -248,95 +1322,944 @@ impl McpSshService {
             }
         };
 
-        // Check if SSH is connected
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        // Consult the session's policy (and, if undecided, the user) before
+        // the command runs.
+        if let Err(result) = self
+            .authorize(
+                params.command.clone(),
+                params.timeout_seconds,
+                Some(connection.clone()),
+            )
+            .await
         {
-            let state = self.ssh_state.read().await;
-            if state.session.is_none() {
-                return CallToolResult::error(vec![Content::text(
-                    "SSH not connected. Call ssh_connect first.",
-                )]);
+            return result;
+        }
+
+        if let Err(e) = self
+            .execute_with_reconnect(&connection, &ssh, &params.command)
+            .await
+        {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Command execution failed: {}",
+                e
+            ))]);
+        }
+
+        // If wait_for_output, read some output
+        if params.wait_for_output {
+            // Give the command time to produce output
+            tokio::time::sleep(Duration::from_millis(500)).await;
+
+            match self.read_output_with_reconnect(&connection, &ssh).await {
+                Ok(Some(output)) => {
+                    // Store in the connection's own buffer
+                    self.ssh_state
+                        .read()
+                        .await
+                        .add_connection_output(&connection, output.clone())
+                        .await;
+
+                    CallToolResult::success(vec![Content::text(format!(
+                        "Command executed successfully.\nOutput:\n{}",
+                        output
+                    ))])
+                }
+                Ok(None) => CallToolResult::success(vec![Content::text(
+                    "Command executed successfully. No immediate output.",
+                )]),
+                Err(e) => CallToolResult::success(vec![Content::text(format!(
+                    "Command executed but failed to read output: {}",
+                    e
+                ))]),
+            }
+        } else {
+            CallToolResult::success(vec![Content::text("Command sent successfully.")])
+        }
+    }
+
+    /// Run `command` on the resolved connection, transparently reconnecting
+    /// with capped exponential backoff if the transport has dropped before
+    /// giving up and surfacing the error to the caller.
+    async fn execute_with_reconnect(
+        &self,
+        connection: &str,
+        ssh: &Arc<Mutex<SshSession>>,
+        command: &str,
+    ) -> anyhow::Result<()> {
+        let first_attempt = ssh.lock().await.execute_command(command.to_string()).await;
+        if let Err(e) = first_attempt {
+            tracing::warn!(
+                "ssh_execute on \"{}\" failed ({}), attempting reconnect",
+                connection,
+                e
+            );
+            self.reconnect_with_backoff(connection).await?;
+            ssh.lock().await.execute_command(command.to_string()).await
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Read output from the resolved connection, with the same
+    /// reconnect-on-failure behavior as `execute_with_reconnect`.
+    async fn read_output_with_reconnect(
+        &self,
+        connection: &str,
+        ssh: &Arc<Mutex<SshSession>>,
+    ) -> anyhow::Result<Option<String>> {
+        let first_attempt = ssh.lock().await.read_output().await;
+        match first_attempt {
+            Ok(output) => Ok(output),
+            Err(e) => {
+                tracing::warn!(
+                    "ssh_read_output on \"{}\" failed ({}), attempting reconnect",
+                    connection,
+                    e
+                );
+                self.reconnect_with_backoff(connection).await?;
+                ssh.lock().await.read_output().await
+            }
+        }
+    }
+
+    /// Retry reconnecting `connection` with capped exponential backoff
+    /// (starting at 500ms, doubling up to the connection's configured
+    /// ceiling, with jitter), up to its configured attempt limit. Emits a
+    /// tracing event and an output-buffer line on each attempt so the UI
+    /// reflects what's happening.
+    async fn reconnect_with_backoff(&self, connection: &str) -> anyhow::Result<()> {
+        let policy = self
+            .ssh_state
+            .read()
+            .await
+            .reconnect_policy(connection)
+            .ok_or_else(|| anyhow::anyhow!("No SSH connection named \"{}\".", connection))?;
+
+        let mut delay = Duration::from_millis(500);
+        for attempt in 1..=policy.max_attempts {
+            let message = format!(
+                "Reconnecting to \"{}\" (attempt {}/{})...",
+                connection, attempt, policy.max_attempts
+            );
+            tracing::warn!("{}", message);
+            self.ssh_state
+                .read()
+                .await
+                .add_connection_output(connection, format!("[reconnect] {}\n", message))
+                .await;
+
+            match self.ssh_state.read().await.reconnect(connection).await {
+                Ok(()) => {
+                    let success = format!("Reconnected to \"{}\".", connection);
+                    tracing::info!("{}", success);
+                    self.ssh_state
+                        .read()
+                        .await
+                        .add_connection_output(connection, format!("[reconnect] {}\n", success))
+                        .await;
+                    return Ok(());
+                }
+                Err(e) if attempt == policy.max_attempts => {
+                    return Err(anyhow::anyhow!(
+                        "Reconnect to \"{}\" failed after {} attempts: {}",
+                        connection,
+                        policy.max_attempts,
+                        e
+                    ));
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Reconnect attempt {} for \"{}\" failed: {}",
+                        attempt,
+                        connection,
+                        e
+                    );
+                    tokio::time::sleep(delay + jitter()).await;
+                    delay = (delay * 2).min(policy.backoff_ceiling);
+                }
             }
         }
 
-        // Request user approval
-        let timeout = Duration::from_secs(params.timeout_seconds);
-        let (approval_id, response_rx) = self
-            .approval_channel
-            .request_approval(params.command.clone())
+        Err(anyhow::anyhow!(
+            "Exceeded max reconnect attempts for \"{}\"",
+            connection
+        ))
+    }
+
+    /// Read recent output from a connected SSH server (see `connection`).
+    async fn tool_ssh_read_output(&self, arguments: Value) -> CallToolResult {
+        let params: SshReadOutputParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, _) = match self.resolve_connection(params.connection.as_deref()).await {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        let output = self
+            .ssh_state
+            .read()
+            .await
+            .get_connection_output(&connection, params.lines)
             .await;
 
-        // Wait for approval with timeout
-        let approved = match tokio::time::timeout(timeout, response_rx).await {
-            Ok(Ok(result)) => result,
-            Ok(Err(_)) => {
-                // Channel closed - treat as rejection
-                return CallToolResult::error(vec![Content::text(
-                    "Approval channel closed unexpectedly.",
-                )]);
+        if output.is_empty() {
+            CallToolResult::success(vec![Content::text("No recent output available.")])
+        } else {
+            CallToolResult::success(vec![Content::text(output.join(""))])
+        }
+    }
+
+    /// List the SSH connections currently open in this session.
+    async fn tool_ssh_list_connections(&self) -> CallToolResult {
+        let connections: Vec<SshConnectionInfo> = self
+            .ssh_state
+            .read()
+            .await
+            .list_connections()
+            .into_iter()
+            .map(|(connection, host, port, username, family)| SshConnectionInfo {
+                connection,
+                host,
+                port,
+                username,
+                family,
+            })
+            .collect();
+
+        if connections.is_empty() {
+            return CallToolResult::success(vec![Content::text("No SSH connections are open.")]);
+        }
+
+        match serde_json::to_string(&connections) {
+            Ok(json) => CallToolResult::success(vec![Content::text(json)]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to serialize connection list: {}",
+                e
+            ))]),
+        }
+    }
+
+    /// Close a named SSH connection.
+    async fn tool_ssh_disconnect(&self, arguments: Value) -> CallToolResult {
+        let params: SshDisconnectParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!("ssh_disconnect {}", params.connection),
+                None,
+                Some(params.connection.clone()),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let entry = {
+            let mut state = self.ssh_state.write().await;
+            state.remove_connection(&params.connection)
+        };
+
+        let Some(entry) = entry else {
+            return CallToolResult::error(vec![Content::text(format!(
+                "No SSH connection named \"{}\". See ssh_list_connections.",
+                params.connection
+            ))]);
+        };
+
+        match Arc::try_unwrap(entry.session) {
+            Ok(mutex) => {
+                if let Err(e) = mutex.into_inner().close().await {
+                    return CallToolResult::error(vec![Content::text(format!(
+                        "Disconnected \"{}\", but the connection didn't close cleanly: {}",
+                        params.connection, e
+                    ))]);
+                }
             }
             Err(_) => {
-                // Timeout
-                self.approval_channel
-                    .broadcast_rejection(approval_id)
+                tracing::warn!(
+                    "ssh_disconnect: connection \"{}\" was still in use elsewhere, dropping without a clean close",
+                    params.connection
+                );
+            }
+        }
+
+        CallToolResult::success(vec![Content::text(format!(
+            "Disconnected \"{}\"",
+            params.connection
+        ))])
+    }
+
+    /// Write raw input to an interactive PTY on a connected SSH server and
+    /// stream its output back incrementally over SSE, rather than the
+    /// single sleep-then-read `ssh_execute` does.
+    async fn tool_ssh_shell(&self, arguments: Value) -> CallToolResult {
+        let params: SshShellParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!("shell input: {}", params.input),
+                params.timeout_seconds,
+                Some(connection.clone()),
+            )
+            .await
+        {
+            return result;
+        }
+
+        if let (Some(cols), Some(rows)) = (params.cols, params.rows) {
+            if let Err(e) = ssh.lock().await.resize(cols, rows).await {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Failed to resize PTY on \"{}\" before writing input: {}",
+                    connection, e
+                ))]);
+            }
+        }
+
+        if let Err(e) = ssh.lock().await.send_input(params.input).await {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to write input to \"{}\": {}",
+                connection, e
+            ))]);
+        }
+
+        self.spawn_shell_output_reader(connection.clone(), ssh);
+
+        CallToolResult::success(vec![Content::text(format!(
+            "Input sent to the PTY on \"{}\". Output is streaming as `ssh_shell_output` events \
+            on this session's SSE endpoint.",
+            connection
+        ))])
+    }
+
+    /// Drain PTY output onto `shell_output_tx` (and the connection's output
+    /// buffer) until no more arrives for 300ms, then stop - a later
+    /// `ssh_shell` call starts a fresh reader. Runs detached
+    /// so `tool_ssh_shell` can return as soon as the input is written rather
+    /// than blocking on however long the remote takes to respond.
+    fn spawn_shell_output_reader(&self, connection: String, ssh: Arc<Mutex<SshSession>>) {
+        let ssh_state = self.ssh_state.clone();
+        let shell_output_tx = self.shell_output_tx.clone();
+        tokio::spawn(async move {
+            loop {
+                let mut guard = ssh.lock().await;
+                let read = tokio::time::timeout(Duration::from_millis(300), guard.read_output()).await;
+                drop(guard);
+
+                let data = match read {
+                    Ok(Ok(Some(data))) => data,
+                    Ok(Ok(None)) | Ok(Err(_)) => break,
+                    Err(_) => break, // quiet for 300ms - nothing more to stream right now
+                };
+
+                ssh_state
+                    .read()
+                    .await
+                    .add_connection_output(&connection, data.clone())
                     .await;
-                return CallToolResult::error(vec![Content::text(
-                    "Approval timeout - command not executed.",
-                )]);
+                let _ = shell_output_tx.send(ShellOutputEvent {
+                    connection: connection.clone(),
+                    data,
+                });
+            }
+        });
+    }
+
+    /// Resize the PTY window on a connected SSH server (see `connection`).
+    async fn tool_ssh_resize(&self, arguments: Value) -> CallToolResult {
+        let params: SshResizeParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        match ssh.lock().await.resize(params.cols, params.rows).await {
+            Ok(()) => CallToolResult::success(vec![Content::text(format!(
+                "Resized \"{}\" to {}x{}",
+                connection, params.cols, params.rows
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to resize \"{}\": {}",
+                connection, e
+            ))]),
+        }
+    }
+
+    /// Send a signal to the foreground process on a connected SSH server's
+    /// PTY, via its terminal control character (e.g. Ctrl-C for `SIGINT`).
+    async fn tool_ssh_send_signal(&self, arguments: Value) -> CallToolResult {
+        let params: SshSendSignalParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
             }
         };
 
-        if !approved {
-            return CallToolResult::error(vec![Content::text("Command rejected by user.")]);
+        let control_byte = match signal_to_control_byte(&params.signal) {
+            Some(byte) => byte,
+            None => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Unsupported signal \"{}\". Supported: SIGINT, SIGQUIT, SIGTSTP.",
+                    params.signal
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!("send signal {}", params.signal),
+                None,
+                Some(connection.clone()),
+            )
+            .await
+        {
+            return result;
         }
 
-        // Execute the approved command
-        let state = self.ssh_state.read().await;
-        if let Some(ssh) = &state.session {
-            let mut ssh_guard = ssh.lock().await;
+        match ssh
+            .lock()
+            .await
+            .send_input((control_byte as char).to_string())
+            .await
+        {
+            Ok(()) => CallToolResult::success(vec![Content::text(format!(
+                "Sent {} to \"{}\"",
+                params.signal, connection
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to send {} to \"{}\": {}",
+                params.signal, connection, e
+            ))]),
+        }
+    }
 
-            if let Err(e) = ssh_guard.execute_command(params.command.clone()).await {
+    /// Open a local port forward (OpenSSH `-L`) on a connected SSH server.
+    async fn tool_ssh_forward_local(&self, arguments: Value) -> CallToolResult {
+        let params: SshForwardLocalParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
                 return CallToolResult::error(vec![Content::text(format!(
-                    "Command execution failed: {}",
+                    "Invalid parameters: {}",
                     e
                 ))]);
             }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
 
-            // If wait_for_output, read some output
-            if params.wait_for_output {
-                // Give the command time to produce output
-                tokio::time::sleep(Duration::from_millis(500)).await;
+        let spec = ForwardSpec {
+            direction: ForwardDirection::LocalToRemote,
+            protocol: params.protocol,
+            bind_addr: params.bind_addr.clone(),
+            bind_port: params.bind_port,
+            target_host: params.target_host.clone(),
+            target_port: params.target_port,
+        };
 
-                match ssh_guard.read_output().await {
-                    Ok(Some(output)) => {
-                        // Store in buffer
-                        drop(ssh_guard);
-                        drop(state);
-                        self.ssh_state.read().await.add_output(output.clone()).await;
+        if let Err(result) = self
+            .authorize(
+                format!(
+                    "forward local {}:{} -> {}:{}",
+                    spec.bind_addr, spec.bind_port, spec.target_host, spec.target_port
+                ),
+                None,
+                Some(connection.clone()),
+            )
+            .await
+        {
+            return result;
+        }
 
-                        CallToolResult::success(vec![Content::text(format!(
-                            "Command executed successfully.\nOutput:\n{}",
-                            output
-                        ))])
-                    }
-                    Ok(None) => CallToolResult::success(vec![Content::text(
-                        "Command executed successfully. No immediate output.",
-                    )]),
-                    Err(e) => CallToolResult::success(vec![Content::text(format!(
-                        "Command executed but failed to read output: {}",
-                        e
-                    ))]),
+        let ssh_guard = ssh.lock().await;
+        match ssh_guard.open_forward(spec).await {
+            Ok(id) => CallToolResult::success(vec![Content::text(format!(
+                "Opened local forward {}:{} -> {}:{} on \"{}\" (id: {})",
+                params.bind_addr, params.bind_port, params.target_host, params.target_port, connection, id
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to open local forward: {}",
+                e
+            ))]),
+        }
+    }
+
+    /// Open a remote port forward (OpenSSH `-R`) on a connected SSH server.
+    async fn tool_ssh_forward_remote(&self, arguments: Value) -> CallToolResult {
+        let params: SshForwardRemoteParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        let spec = ForwardSpec {
+            direction: ForwardDirection::RemoteToLocal,
+            protocol: ForwardProtocol::Tcp,
+            bind_addr: params.bind_addr.clone(),
+            bind_port: params.bind_port,
+            target_host: params.target_host.clone(),
+            target_port: params.target_port,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!(
+                    "forward remote {}:{} -> {}:{}",
+                    spec.bind_addr, spec.bind_port, spec.target_host, spec.target_port
+                ),
+                None,
+                Some(connection.clone()),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let ssh_guard = ssh.lock().await;
+        match ssh_guard.open_forward(spec).await {
+            Ok(id) => CallToolResult::success(vec![Content::text(format!(
+                "Opened remote forward {}:{} -> {}:{} on \"{}\" (id: {})",
+                params.bind_addr, params.bind_port, params.target_host, params.target_port, connection, id
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to open remote forward: {}",
+                e
+            ))]),
+        }
+    }
+
+    /// Close a port forward previously opened by `ssh_forward_local`/
+    /// `ssh_forward_remote`.
+    async fn tool_ssh_cancel_forward(&self, arguments: Value) -> CallToolResult {
+        let params: SshCancelForwardParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let id = match Uuid::parse_str(&params.id) {
+            Ok(id) => id,
+            Err(_) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid forward id \"{}\"",
+                    params.id
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!("cancel forward {}", id),
+                None,
+                Some(connection.clone()),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let ssh_guard = ssh.lock().await;
+        if ssh_guard.close_forward(id).await {
+            CallToolResult::success(vec![Content::text(format!(
+                "Closed forward {} on \"{}\"",
+                id, connection
+            ))])
+        } else {
+            CallToolResult::error(vec![Content::text(format!(
+                "No forward with id {} on \"{}\"",
+                id, connection
+            ))])
+        }
+    }
+
+    /// Read a file on the connected SSH server over SFTP.
+    async fn tool_fs_read_file(&self, arguments: Value) -> CallToolResult {
+        let params: FsReadFileParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize_read(
+                format!("fs_read_file {}", params.path),
+                Some(connection),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let sftp = match self.open_sftp(&ssh).await {
+            Ok(sftp) => sftp,
+            Err(result) => return result,
+        };
+
+        let mut file = match sftp.open(&params.path).await {
+            Ok(file) => file,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Failed to open {}: {}",
+                    params.path, e
+                ))]);
+            }
+        };
+
+        let mut contents = Vec::new();
+        if let Err(e) = file.read_to_end(&mut contents).await {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to read {}: {}",
+                params.path, e
+            ))]);
+        }
+
+        CallToolResult::success(vec![Content::text(
+            String::from_utf8_lossy(&contents).to_string(),
+        )])
+    }
+
+    /// Write (or append to) a file on the connected SSH server over SFTP.
+    async fn tool_fs_write_file(&self, arguments: Value) -> CallToolResult {
+        let params: FsWriteFileParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!(
+                    "fs_write_file {} ({} bytes)",
+                    params.path,
+                    params.content.len()
+                ),
+                None,
+                Some(connection),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let sftp = match self.open_sftp(&ssh).await {
+            Ok(sftp) => sftp,
+            Err(result) => return result,
+        };
+
+        let flags = if params.append {
+            OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::APPEND
+        } else {
+            OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE
+        };
+
+        let mut file = match sftp.open_with_flags(&params.path, flags).await {
+            Ok(file) => file,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Failed to open {}: {}",
+                    params.path, e
+                ))]);
+            }
+        };
+
+        if let Err(e) = file.write_all(params.content.as_bytes()).await {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to write {}: {}",
+                params.path, e
+            ))]);
+        }
+        if let Err(e) = file.shutdown().await {
+            return CallToolResult::error(vec![Content::text(format!(
+                "Failed to flush {}: {}",
+                params.path, e
+            ))]);
+        }
+
+        CallToolResult::success(vec![Content::text(format!("Wrote {}", params.path))])
+    }
+
+    /// List a directory on the connected SSH server over SFTP.
+    async fn tool_fs_list_dir(&self, arguments: Value) -> CallToolResult {
+        let params: FsListDirParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize_read(
+                format!("fs_list_dir {}", params.path),
+                Some(connection),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let sftp = match self.open_sftp(&ssh).await {
+            Ok(sftp) => sftp,
+            Err(result) => return result,
+        };
+
+        let entries = match sftp.read_dir(&params.path).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Failed to list {}: {}",
+                    params.path, e
+                ))]);
+            }
+        };
+
+        let listing: Vec<FsDirEntry> = entries
+            .map(|entry| {
+                let metadata = entry.metadata();
+                FsDirEntry {
+                    name: entry.file_name(),
+                    is_dir: metadata.is_dir(),
+                    size: metadata.size.unwrap_or(0),
+                    mtime: metadata.mtime.unwrap_or(0) as u64,
+                    mode: metadata.permissions.unwrap_or(0),
                 }
+            })
+            .collect();
+
+        match serde_json::to_string(&listing) {
+            Ok(json) => CallToolResult::success(vec![Content::text(json)]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to serialize directory listing: {}",
+                e
+            ))]),
+        }
+    }
+
+    /// Rename or move a file/directory on the connected SSH server over SFTP.
+    async fn tool_fs_rename(&self, arguments: Value) -> CallToolResult {
+        let params: FsRenameParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!(
+                    "fs_rename {} -> {}",
+                    params.from, params.to
+                ),
+                None,
+                Some(connection),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let sftp = match self.open_sftp(&ssh).await {
+            Ok(sftp) => sftp,
+            Err(result) => return result,
+        };
+
+        match sftp.rename(&params.from, &params.to).await {
+            Ok(()) => CallToolResult::success(vec![Content::text(format!(
+                "Renamed {} to {}",
+                params.from, params.to
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to rename {} to {}: {}",
+                params.from, params.to, e
+            ))]),
+        }
+    }
+
+    /// Delete a file, or a directory (with `recursive: true`), on the
+    /// connected SSH server over SFTP.
+    async fn tool_fs_delete(&self, arguments: Value) -> CallToolResult {
+        let params: FsDeleteParams = match serde_json::from_value(arguments) {
+            Ok(p) => p,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Invalid parameters: {}",
+                    e
+                ))]);
+            }
+        };
+
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
+
+        if let Err(result) = self
+            .authorize(
+                format!("fs_delete {}", params.path),
+                None,
+                Some(connection),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let sftp = match self.open_sftp(&ssh).await {
+            Ok(sftp) => sftp,
+            Err(result) => return result,
+        };
+
+        let metadata = match sftp.metadata(&params.path).await {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                return CallToolResult::error(vec![Content::text(format!(
+                    "Failed to stat {}: {}",
+                    params.path, e
+                ))]);
+            }
+        };
+
+        let result = if metadata.is_dir() {
+            if params.recursive {
+                remove_dir_recursive(&sftp, params.path.clone()).await
             } else {
-                CallToolResult::success(vec![Content::text("Command sent successfully.")])
+                sftp.remove_dir(&params.path).await
             }
         } else {
-            CallToolResult::error(vec![Content::text("SSH session lost.")])
+            sftp.remove_file(&params.path).await
+        };
+
+        match result {
+            Ok(()) => CallToolResult::success(vec![Content::text(format!(
+                "Deleted {}",
+                params.path
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to delete {}: {}",
+                params.path, e
+            ))]),
         }
     }
 
-    /// Read recent output from the SSH terminal session.
-    async fn tool_ssh_read_output(&self, arguments: Value) -> CallToolResult {
-        let params: SshReadOutputParams = match serde_json::from_value(arguments) {
+    /// Create a directory (optionally with missing parents) on the
+    /// connected SSH server over SFTP.
+    async fn tool_fs_mkdir(&self, arguments: Value) -> CallToolResult {
+        let params: FsMkdirParams = match serde_json::from_value(arguments) {
             Ok(p) => p,
             Err(e) => {
                 return CallToolResult::error(vec![Content::text(format!(
@@ -346,13 +2269,43 @@ impl McpSshService {
             }
         };
 
-        let state = self.ssh_state.read().await;
-        let output = state.get_recent_output(params.lines).await;
+        let (connection, ssh) = match self.resolve_connection(params.connection.as_deref()).await
+        {
+            Ok(resolved) => resolved,
+            Err(result) => return result,
+        };
 
-        if output.is_empty() {
-            CallToolResult::success(vec![Content::text("No recent output available.")])
+        if let Err(result) = self
+            .authorize(
+                format!("fs_mkdir {}", params.path),
+                None,
+                Some(connection),
+            )
+            .await
+        {
+            return result;
+        }
+
+        let sftp = match self.open_sftp(&ssh).await {
+            Ok(sftp) => sftp,
+            Err(result) => return result,
+        };
+
+        let result = if params.recursive {
+            mkdir_recursive(&sftp, &params.path).await
         } else {
-            CallToolResult::success(vec![Content::text(output.join(""))])
+            sftp.create_dir(&params.path).await
+        };
+
+        match result {
+            Ok(()) => CallToolResult::success(vec![Content::text(format!(
+                "Created directory {}",
+                params.path
+            ))]),
+            Err(e) => CallToolResult::error(vec![Content::text(format!(
+                "Failed to create directory {}: {}",
+                params.path, e
+            ))]),
         }
     }
 
@@ -361,3 +2314,90 @@ impl McpSshService {
         self.ssh_state.clone()
     }
 }
+
+/// Recursively delete a non-empty directory (SFTP has no `rm -r`): walk it
+/// depth-first so every file and subdirectory is removed before `path`
+/// itself. Boxed because `async fn` can't recurse directly.
+fn remove_dir_recursive(
+    sftp: &SftpSession,
+    path: String,
+) -> BoxFuture<'_, Result<(), russh_sftp::client::error::Error>> {
+    Box::pin(async move {
+        let entries = sftp.read_dir(&path).await?;
+        for entry in entries {
+            let name = entry.file_name();
+            if name == "." || name == ".." {
+                continue;
+            }
+            let child = format!("{}/{}", path.trim_end_matches('/'), name);
+            if entry.metadata().is_dir() {
+                remove_dir_recursive(sftp, child).await?;
+            } else {
+                sftp.remove_file(&child).await?;
+            }
+        }
+        sftp.remove_dir(&path).await
+    })
+}
+
+/// Create `path` and any missing parent directories, like `mkdir -p`.
+async fn mkdir_recursive(
+    sftp: &SftpSession,
+    path: &str,
+) -> Result<(), russh_sftp::client::error::Error> {
+    let is_absolute = path.starts_with('/');
+    let mut built = String::new();
+    for segment in path.split('/').filter(|c| !c.is_empty()) {
+        if !built.is_empty() || is_absolute {
+            built.push('/');
+        }
+        built.push_str(segment);
+
+        if sftp
+            .metadata(&built)
+            .await
+            .map(|m| m.is_dir())
+            .unwrap_or(false)
+        {
+            continue;
+        }
+        sftp.create_dir(&built).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Regression test for a bug where `authorize()` call sites embedded the
+    /// named connection into the action text (`"[host:22] git status"`),
+    /// which `ApprovalPolicy::evaluate`'s anchored glob match could never
+    /// match against a plain `"git *"` allow pattern - silently neutering
+    /// the allow/deny-list feature for every connection-scoped tool. The
+    /// connection is already carried separately via `authorize`'s
+    /// `connection` parameter, so the action text itself must stay bare.
+    #[tokio::test]
+    async fn test_authorize_allow_pattern_matches_with_named_connection() {
+        let policy = Arc::new(RwLock::new(ApprovalPolicy {
+            allow_patterns: vec!["git *".to_string()],
+            ..ApprovalPolicy::default()
+        }));
+        let service = McpSshService::new(
+            Uuid::new_v4(),
+            Arc::new(ApprovalChannel::new()),
+            policy,
+            Arc::new(RwLock::new(Instant::now())),
+        );
+
+        let result = service
+            .authorize(
+                "git status".to_string(),
+                None,
+                Some("host:22".to_string()),
+            )
+            .await;
+
+        assert!(result.is_ok());
+    }
+}
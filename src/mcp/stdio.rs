@@ -0,0 +1,115 @@
+//! Stdio MCP transport.
+//!
+//! Speaks the same newline-delimited JSON-RPC dispatch
+//! (`crate::mcp::http::handle_rpc`) as the HTTP transport, but reads
+//! requests from stdin and writes responses to stdout. This lets the crate
+//! run as a standard local MCP server launched directly by an editor/agent,
+//! without a network round-trip or session UUID.
+
+use crate::mcp::http::{handle_rpc, JsonRpcRequest};
+use crate::mcp::{ApprovalChannel, ApprovalPolicy, McpSshService};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::RwLock;
+
+/// Run the stdio transport until stdin is closed.
+///
+/// Each line of stdin must be a single JSON-RPC request object; each
+/// response is written back as a single line of JSON on stdout.
+pub async fn run() -> anyhow::Result<()> {
+    let approval_channel = Arc::new(ApprovalChannel::new());
+    let policy = Arc::new(RwLock::new(ApprovalPolicy::default()));
+    let last_active = Arc::new(RwLock::new(std::time::Instant::now()));
+    let service = McpSshService::new(uuid::Uuid::new_v4(), approval_channel, policy, last_active);
+
+    let stdin = tokio::io::stdin();
+    let mut stdout = tokio::io::stdout();
+    let mut lines = BufReader::new(stdin).lines();
+
+    tracing::info!("MCP stdio transport ready");
+
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let request: JsonRpcRequest = match serde_json::from_str(line) {
+            Ok(req) => req,
+            Err(e) => {
+                tracing::warn!("Failed to parse stdio JSON-RPC request: {}", e);
+                continue;
+            }
+        };
+
+        // Notifications (no `id`) don't get a response.
+        let has_id = request_has_id(line);
+        let response = handle_rpc(&service, request).await;
+        if has_id {
+            let json = serde_json::to_string(&response)?;
+            stdout.write_all(json.as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await?;
+        }
+    }
+
+    tracing::info!("MCP stdio transport closed (stdin EOF)");
+    Ok(())
+}
+
+/// Cheap check for whether a raw request line carries a JSON-RPC `id`,
+/// without re-parsing it through `JsonRpcRequest` (which defaults `id` to
+/// `None` for both "absent" and "null").
+fn request_has_id(line: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(line)
+        .ok()
+        .and_then(|v| v.get("id").cloned())
+        .is_some()
+}
+
+/// Windows named-pipe MCP transport, carrying the same newline-delimited
+/// JSON-RPC framing as stdio. Useful for local IPC with editors that prefer
+/// connecting to a named pipe over inheriting stdio handles.
+#[cfg(windows)]
+pub async fn run_named_pipe(pipe_name: &str) -> anyhow::Result<()> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    loop {
+        let mut server = ServerOptions::new().create(pipe_name)?;
+        server.connect().await?;
+
+        let approval_channel = Arc::new(ApprovalChannel::new());
+        let policy = Arc::new(RwLock::new(ApprovalPolicy::default()));
+        let last_active = Arc::new(RwLock::new(std::time::Instant::now()));
+        let service = McpSshService::new(uuid::Uuid::new_v4(), approval_channel, policy, last_active);
+
+        tokio::spawn(async move {
+            let (reader, mut writer) = tokio::io::split(server);
+            let mut lines = BufReader::new(reader).lines();
+
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) => {
+                        let line = line.trim();
+                        if line.is_empty() {
+                            continue;
+                        }
+                        let Ok(request) = serde_json::from_str::<JsonRpcRequest>(line) else {
+                            continue;
+                        };
+                        let has_id = request_has_id(line);
+                        let response = handle_rpc(&service, request).await;
+                        if has_id {
+                            if let Ok(json) = serde_json::to_string(&response) {
+                                let _ = writer.write_all(json.as_bytes()).await;
+                                let _ = writer.write_all(b"\n").await;
+                                let _ = writer.flush().await;
+                            }
+                        }
+                    }
+                    _ => break,
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,168 @@
+//! Versioned WebSocket handshake so frontend and backend can detect
+//! protocol incompatibility before exchanging `TerminalMessage`/
+//! `CommandMessage`/`LspMessage` frames, rather than silently mis-parsing
+//! them.
+//!
+//! Every WebSocket upgrade handler in `websocket.rs` starts the connection
+//! by exchanging a `Hello` in each direction: the server sends its own
+//! first, then waits for the client's. If the client's `protocol_version`
+//! major component doesn't match ours, the server replies `Incompatible`
+//! and closes instead of proceeding, so an old UI talking a newer/older
+//! message schema fails loudly rather than misinterpreting variants it
+//! doesn't understand.
+
+use axum::extract::ws::{Message, WebSocket};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Current protocol version. Bump the major component for breaking
+/// `TerminalMessage`/`CommandMessage`/`LspMessage` schema changes; bump
+/// the minor component for additive, backward-compatible ones.
+pub const PROTOCOL_VERSION: &str = "1.0";
+
+/// Oldest client major version this server still accepts.
+const MIN_SUPPORTED_VERSION: &str = "1.0";
+
+/// Every capability this server can negotiate. A client advertising an
+/// unknown capability simply has it dropped from the negotiated set; an
+/// older client omitting a newer one means handlers gate on its absence.
+const SERVER_CAPABILITIES: &[&str] = &[
+    "ssh",
+    "lsp",
+    "port_forward",
+    "approval_policies",
+    "scrollback_replay",
+];
+
+/// The handshake frame exchanged by both sides, and the server's rejection
+/// of an incompatible client.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HandshakeMessage {
+    /// Sent by both sides as the very first frame on every upgraded
+    /// WebSocket. `capabilities` advertises optional features the sender
+    /// supports (e.g. `ssh`, `lsp`, `port_forward`).
+    Hello {
+        protocol_version: String,
+        capabilities: Vec<String>,
+    },
+    /// Sent by the server instead of proceeding, when the client's major
+    /// `protocol_version` doesn't match ours.
+    Incompatible {
+        server_version: String,
+        min_supported: String,
+    },
+}
+
+/// Capabilities negotiated for one connection: the intersection of what
+/// the client advertised and what this server supports. Handlers consult
+/// this to gate newer message variants (e.g. reject a `ForwardOpen` if
+/// `port_forward` wasn't negotiated) instead of assuming every connected
+/// client understands them.
+#[derive(Debug, Clone, Default)]
+pub struct NegotiatedCapabilities(HashSet<String>);
+
+impl NegotiatedCapabilities {
+    pub fn has(&self, capability: &str) -> bool {
+        self.0.contains(capability)
+    }
+}
+
+/// Why the handshake didn't produce a usable connection. Either way the
+/// caller should just return from the WebSocket handler; in the
+/// `VersionMismatch` case the client has already been told why.
+#[derive(Debug)]
+pub enum HandshakeError {
+    /// The client's major `protocol_version` didn't match ours.
+    VersionMismatch,
+    /// The socket closed or errored before a valid `Hello` arrived.
+    Disconnected,
+}
+
+/// The major version component, e.g. `"2"` out of `"2.3"`.
+fn major_version(version: &str) -> &str {
+    version.split('.').next().unwrap_or(version)
+}
+
+/// Run the handshake on a freshly upgraded socket: send our `Hello`, wait
+/// for the client's, and check version compatibility. On success, returns
+/// the negotiated capability set and the socket, ready for the handler's
+/// normal split/relay loop.
+pub async fn handshake(
+    mut socket: WebSocket,
+) -> Result<(NegotiatedCapabilities, WebSocket), HandshakeError> {
+    let hello = HandshakeMessage::Hello {
+        protocol_version: PROTOCOL_VERSION.to_string(),
+        capabilities: SERVER_CAPABILITIES.iter().map(|s| s.to_string()).collect(),
+    };
+    if socket
+        .send(Message::Text(serde_json::to_string(&hello).unwrap()))
+        .await
+        .is_err()
+    {
+        return Err(HandshakeError::Disconnected);
+    }
+
+    let (client_version, client_capabilities) = loop {
+        match socket.next().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str::<HandshakeMessage>(&text)
+            {
+                Ok(HandshakeMessage::Hello {
+                    protocol_version,
+                    capabilities,
+                }) => break (protocol_version, capabilities),
+                // Not a `Hello` - the client hasn't spoken yet, keep waiting.
+                _ => continue,
+            },
+            Some(Ok(Message::Close(_))) | None => return Err(HandshakeError::Disconnected),
+            Some(Ok(_)) => continue,
+            Some(Err(_)) => return Err(HandshakeError::Disconnected),
+        }
+    };
+
+    if major_version(&client_version) != major_version(PROTOCOL_VERSION) {
+        let incompatible = HandshakeMessage::Incompatible {
+            server_version: PROTOCOL_VERSION.to_string(),
+            min_supported: MIN_SUPPORTED_VERSION.to_string(),
+        };
+        let _ = socket
+            .send(Message::Text(serde_json::to_string(&incompatible).unwrap()))
+            .await;
+        let _ = socket.close().await;
+        return Err(HandshakeError::VersionMismatch);
+    }
+
+    let negotiated = client_capabilities
+        .into_iter()
+        .filter(|c| SERVER_CAPABILITIES.contains(&c.as_str()))
+        .collect();
+
+    Ok((NegotiatedCapabilities(negotiated), socket))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_major_version() {
+        assert_eq!(major_version("1.0"), "1");
+        assert_eq!(major_version("2.3"), "2");
+        assert_eq!(major_version("7"), "7");
+    }
+
+    #[test]
+    fn test_negotiated_capabilities_filters_unknown() {
+        let negotiated: NegotiatedCapabilities = NegotiatedCapabilities(
+            ["ssh", "made_up_feature"]
+                .into_iter()
+                .filter(|c| SERVER_CAPABILITIES.contains(c))
+                .map(String::from)
+                .collect(),
+        );
+        assert!(negotiated.has("ssh"));
+        assert!(!negotiated.has("made_up_feature"));
+        assert!(!negotiated.has("lsp"));
+    }
+}
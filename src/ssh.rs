@@ -1,36 +1,440 @@
+use crate::forward::{ForwardRegistry, ForwardSpec};
+use crate::known_hosts::{default_known_hosts_path, HostKeyError, HostKeyPolicy, KnownHostsStore};
 use anyhow::{Context, Result};
-use russh::client::{self, Handle};
-use russh::keys::{decode_secret_key, PrivateKeyWithHashAlg};
+use futures::future::BoxFuture;
+use russh::client::{self, AuthResult, Handle, KeyboardInteractiveAuthResponse};
+use russh::keys::agent::client::AgentClient;
+use russh::keys::{decode_secret_key, HashAlg, PrivateKeyWithHashAlg};
 use russh::*;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::io::Cursor;
+use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use uuid::Uuid;
 
+#[derive(Clone)]
 pub struct SshConfig {
     pub host: String,
     pub port: u16,
     pub username: String,
     pub password: Option<String>,
     pub private_key: Option<String>,
+    /// Passphrase to decrypt `private_key`, if it's encrypted.
+    pub passphrase: Option<String>,
+    /// Authenticate against the user's running ssh-agent instead of
+    /// `private_key`/`password`: the agent signs the auth challenge and the
+    /// key material never crosses this API.
+    pub use_agent: bool,
+    /// Agent socket path to connect to (e.g. a non-default `$SSH_AUTH_SOCK`).
+    /// Falls back to the `SSH_AUTH_SOCK` environment variable when omitted.
+    pub agent_socket: Option<String>,
+    /// Forward the agent connection to the remote (OpenSSH's `-A`), so
+    /// commands run there can themselves authenticate against it. Only
+    /// meaningful alongside `use_agent`.
+    pub forward_agent: bool,
+    /// Terminal type sent in the `pty-req`, so remote programs pick the
+    /// right terminfo entry instead of whatever the hardcoded default was.
+    pub term: String,
+    /// Initial PTY size sent in the `pty-req`; later resized via `resize`.
+    pub cols: u32,
+    pub rows: u32,
+    /// How to handle an unknown or changed host key - see
+    /// `crate::known_hosts`.
+    pub host_key_policy: HostKeyPolicy,
+    /// Path to the known_hosts-style file recording trusted host key
+    /// fingerprints. Defaults to `known_hosts::default_known_hosts_path()`
+    /// when `None`.
+    pub known_hosts_path: Option<PathBuf>,
+    /// Ordered authentication methods to try against this connection, each
+    /// attempted on the same session so a server requiring more than one
+    /// (e.g. a public key, then a keyboard-interactive OTP prompt) chains
+    /// naturally from one to the next. Empty means "infer a single legacy
+    /// method from `use_agent`/`password`/`private_key`", so every caller
+    /// predating this field keeps behaving exactly as before.
+    pub auth_methods: Vec<AuthMethod>,
+    /// Answers keyboard-interactive prompts when `auth_methods` includes
+    /// `AuthMethod::KeyboardInteractive`. Required in that case, ignored
+    /// otherwise.
+    pub keyboard_interactive: Option<KeyboardInteractiveHandler>,
+}
+
+/// Which authentication method to try next in `SshConfig::auth_methods`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthMethod {
+    /// Sign the auth challenge via the user's running ssh-agent.
+    Agent,
+    Password,
+    PublicKey,
+    /// Drive a PAM-style challenge/response exchange - including TOTP/2FA
+    /// prompts - via `SshConfig::keyboard_interactive`.
+    KeyboardInteractive,
+}
+
+/// One keyboard-interactive prompt the server asked for - e.g. "Password:"
+/// or a TOTP code - mirroring PAM's prompt/echo model (`echo` is false for
+/// input that shouldn't be displayed as it's typed).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthPrompt {
+    pub prompt: String,
+    pub echo: bool,
+}
+
+/// Answers one round of a keyboard-interactive exchange: given the
+/// server's `name`/`instructions` and `prompts`, returns one answer per
+/// prompt in the same order, or `None` if no answer arrived before the
+/// caller's own timeout. Boxed so `SshConfig` can hold one without a
+/// generic parameter leaking into every caller that only ever uses
+/// password/public-key/agent auth.
+pub type KeyboardInteractiveHandler = Arc<
+    dyn Fn(String, String, Vec<AuthPrompt>) -> BoxFuture<'static, Option<Vec<String>>>
+        + Send
+        + Sync,
+>;
+
+/// Distinguishes *why* authentication failed, so callers (the REST
+/// `ssh_connect_handler`) can report bad-passphrase/agent-unavailable/
+/// rejected-by-server separately instead of one generic message.
+#[derive(Debug)]
+pub enum SshAuthError {
+    /// `private_key` failed to decode, most likely because `passphrase`
+    /// was missing or wrong.
+    BadPassphrase,
+    /// The ssh-agent couldn't be reached, or had no usable identities.
+    AgentUnavailable(String),
+    /// The server rejected every offered credential.
+    AuthRejected,
+    /// The server rejected every identity the ssh-agent offered. Lists the
+    /// fingerprint of each attempted identity, since "auth rejected" alone
+    /// doesn't tell the user which of their agent's keys were tried.
+    AgentAuthRejected(Vec<String>),
+    /// No answer arrived for a keyboard-interactive prompt round before the
+    /// caller's own timeout.
+    KeyboardInteractiveTimeout,
+}
+
+impl std::fmt::Display for SshAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SshAuthError::BadPassphrase => {
+                write!(f, "Failed to decrypt private key: incorrect passphrase")
+            }
+            SshAuthError::AgentUnavailable(reason) => {
+                write!(f, "SSH agent unavailable: {}", reason)
+            }
+            SshAuthError::AuthRejected => write!(f, "Authentication rejected by server"),
+            SshAuthError::AgentAuthRejected(attempted) => write!(
+                f,
+                "Authentication rejected by server (tried agent identities: {})",
+                attempted.join(", ")
+            ),
+            SshAuthError::KeyboardInteractiveTimeout => {
+                write!(f, "Timed out waiting for a keyboard-interactive auth response")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SshAuthError {}
+
+/// Coarse remote OS family, detected once at connect time by probing the
+/// remote end. `execute_command`'s line terminator (`\n` vs `\r\n`) and
+/// path-sensitive tooling both need to know which one they're talking to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SshFamily {
+    Unix,
+    Windows,
+}
+
+impl std::fmt::Display for SshFamily {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SshFamily::Unix => write!(f, "Unix"),
+            SshFamily::Windows => write!(f, "Windows"),
+        }
+    }
 }
 
 pub struct SshSession {
-    #[allow(dead_code)]
     handle: Handle<Client>,
     channel: Channel<client::Msg>,
+    /// Active TCP/UDP port forwards tunneled through `handle`.
+    forwards: ForwardRegistry,
+    family: SshFamily,
 }
 
-struct Client;
+/// The client-side SSH handler.
+///
+/// Besides the usual host-key check, it also accepts forwarded-tcpip
+/// channels the server opens in response to a `RemoteToLocal` forward's
+/// `tcpip_forward` request, routing each to the local target registered in
+/// `remote_targets` for its listen port.
+pub(crate) struct Client {
+    remote_targets: Arc<Mutex<HashMap<u32, (String, u16)>>>,
+    host: String,
+    port: u16,
+    known_hosts: Arc<KnownHostsStore>,
+    host_key_policy: HostKeyPolicy,
+    /// Stashes a rejected host key's `HostKeyError` for `SshSession::connect`
+    /// to surface after the handshake fails, since `check_server_key` itself
+    /// can only return `Ok(bool)`.
+    host_key_error: Arc<Mutex<Option<HostKeyError>>>,
+}
 
 impl client::Handler for Client {
     type Error = russh::Error;
 
     fn check_server_key(
         &mut self,
-        _server_public_key: &russh::keys::PublicKey,
+        server_public_key: &russh::keys::PublicKey,
     ) -> impl std::future::Future<Output = Result<bool, Self::Error>> + Send {
-        // In production, you should verify the server key properly
-        async { Ok(true) }
+        let fingerprint = server_public_key.fingerprint(HashAlg::Sha256).to_string();
+        let host = self.host.clone();
+        let port = self.port;
+        let known_hosts = self.known_hosts.clone();
+        let policy = self.host_key_policy;
+        let host_key_error = self.host_key_error.clone();
+        async move {
+            match known_hosts.verify(&host, port, &fingerprint, policy).await {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    tracing::warn!("Host key verification failed for {}:{}: {}", host, port, e);
+                    *host_key_error.lock().await = Some(e);
+                    Ok(false)
+                }
+            }
+        }
+    }
+
+    fn channel_open_forwarded_tcpip(
+        &mut self,
+        channel: Channel<client::Msg>,
+        connected_address: &str,
+        connected_port: u32,
+        originator_address: &str,
+        originator_port: u32,
+        _session: &mut client::Session,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send {
+        let remote_targets = self.remote_targets.clone();
+        let connected_address = connected_address.to_string();
+        let originator_address = originator_address.to_string();
+        async move {
+            let target = remote_targets.lock().await.get(&connected_port).cloned();
+            let Some((target_host, target_port)) = target else {
+                tracing::warn!(
+                    "Forwarded channel for unregistered remote port {} (from {}:{}, via {})",
+                    connected_port,
+                    originator_address,
+                    originator_port,
+                    connected_address
+                );
+                return Ok(());
+            };
+
+            match TcpStream::connect((target_host.as_str(), target_port)).await {
+                Ok(stream) => {
+                    tokio::spawn(pump_forwarded_channel(stream, channel));
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to connect local forward target {}:{}: {}",
+                        target_host,
+                        target_port,
+                        e
+                    );
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Pump bytes between a locally-connected `stream` and a server-initiated
+/// forwarded-tcpip `channel` (the remote-to-local counterpart of
+/// `forward::pump_tcp_channel`, which handles the local-to-remote direction).
+async fn pump_forwarded_channel(mut stream: TcpStream, mut channel: Channel<client::Msg>) {
+    let mut buf = vec![0u8; 8192];
+    loop {
+        tokio::select! {
+            result = stream.read(&mut buf) => {
+                match result {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => {
+                        if channel.data(Cursor::new(buf[..n].to_vec())).await.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            msg = channel.wait() => {
+                match msg {
+                    Some(ChannelMsg::Data { data }) => {
+                        if stream.write_all(&data).await.is_err() {
+                            break;
+                        }
+                    }
+                    Some(ChannelMsg::Eof) | None => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Authenticate by enumerating the ssh-agent's loaded identities and asking
+/// it to sign the auth challenge for each in turn, so the private key
+/// material never crosses this process. Connects over `agent_socket` (or
+/// the platform default - `$SSH_AUTH_SOCK` on Unix, the OpenSSH-for-Windows
+/// named pipe on Windows) when unset.
+async fn authenticate_with_agent(
+    session: &mut Handle<Client>,
+    config: &SshConfig,
+) -> Result<AuthResult> {
+    #[cfg(windows)]
+    let connected = {
+        let pipe_name = config
+            .agent_socket
+            .clone()
+            .unwrap_or_else(|| r"\\.\pipe\openssh-ssh-agent".to_string());
+        AgentClient::connect_named_pipe(pipe_name).await
+    };
+    #[cfg(not(windows))]
+    let connected = match &config.agent_socket {
+        Some(path) => AgentClient::connect_uds(path).await,
+        None => AgentClient::connect_env().await,
+    };
+
+    let mut agent = connected
+        .map_err(|e| anyhow::Error::new(SshAuthError::AgentUnavailable(e.to_string())))?;
+
+    let identities = agent
+        .request_identities()
+        .await
+        .map_err(|e| anyhow::Error::new(SshAuthError::AgentUnavailable(e.to_string())))?;
+
+    if identities.is_empty() {
+        return Err(anyhow::Error::new(SshAuthError::AgentUnavailable(
+            "agent has no loaded identities".to_string(),
+        )));
+    }
+
+    let mut attempted = Vec::new();
+    for identity in identities {
+        attempted.push(identity.fingerprint(HashAlg::Sha256).to_string());
+        let (returned_agent, result) = session
+            .authenticate_future(config.username.clone(), identity, agent)
+            .await;
+        agent = returned_agent;
+        if let Ok(auth_result) = result {
+            if auth_result.success() {
+                return Ok(auth_result);
+            }
+        }
+    }
+
+    Err(anyhow::Error::new(SshAuthError::AgentAuthRejected(attempted)))
+}
+
+/// Infer a single legacy auth method from the pre-`auth_methods` config
+/// fields, so every caller that only ever set one of `use_agent`/
+/// `password`/`private_key` (i.e. every caller predating `auth_methods`)
+/// keeps behaving exactly as before.
+fn legacy_auth_methods(config: &SshConfig) -> Vec<AuthMethod> {
+    if config.use_agent {
+        vec![AuthMethod::Agent]
+    } else if config.password.is_some() {
+        vec![AuthMethod::Password]
+    } else if config.private_key.is_some() {
+        vec![AuthMethod::PublicKey]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Drive russh's keyboard-interactive exchange to completion: start it,
+/// and for each round the server sends, hand its prompts to
+/// `config.keyboard_interactive` and feed the answers back, looping until
+/// the server reports success or failure. Used for PAM challenges -
+/// including TOTP/2FA prompts - that `authenticate_password` can't express.
+/// Returns `Ok(true)`/`Ok(false)` for success/failure rather than the
+/// other methods' `AuthResult`, since there's no public way to construct
+/// one outside the successful handshake path itself.
+async fn authenticate_keyboard_interactive(
+    session: &mut Handle<Client>,
+    config: &SshConfig,
+) -> Result<bool> {
+    let handler = config.keyboard_interactive.clone().ok_or_else(|| {
+        anyhow::anyhow!("keyboard-interactive auth requested but no handler configured")
+    })?;
+
+    let mut response = session
+        .authenticate_keyboard_interactive_start(config.username.clone(), None)
+        .await
+        .context("Failed to start keyboard-interactive authentication")?;
+
+    loop {
+        let (name, instructions, prompts) = match response {
+            KeyboardInteractiveAuthResponse::Success => return Ok(true),
+            KeyboardInteractiveAuthResponse::Failure => return Ok(false),
+            KeyboardInteractiveAuthResponse::InfoRequest {
+                name,
+                instructions,
+                prompts,
+            } => (name, instructions, prompts),
+        };
+
+        let prompts = prompts
+            .into_iter()
+            .map(|p| AuthPrompt {
+                prompt: p.prompt,
+                echo: p.echo,
+            })
+            .collect();
+
+        let Some(answers) = handler(name, instructions, prompts).await else {
+            return Err(anyhow::Error::new(SshAuthError::KeyboardInteractiveTimeout));
+        };
+
+        response = session
+            .authenticate_keyboard_interactive_respond(answers)
+            .await
+            .context("Failed to respond to keyboard-interactive challenge")?;
+    }
+}
+
+/// Classify the remote end as Unix or Windows by running `uname -s` on a
+/// dedicated exec channel: a real shell output like "Linux" or "Darwin"
+/// means Unix, while a missing/unrecognized command (the common case on a
+/// Windows OpenSSH server, whose default shell doesn't have `uname`) means
+/// Windows.
+async fn detect_family(handle: &Handle<Client>) -> SshFamily {
+    match probe_exec(handle, "uname -s").await {
+        Some(output) if !output.trim().is_empty() => SshFamily::Unix,
+        _ => SshFamily::Windows,
+    }
+}
+
+/// Run `command` on a fresh exec channel and collect its output, returning
+/// `None` if the channel couldn't be opened/run at all.
+async fn probe_exec(handle: &Handle<Client>, command: &str) -> Option<String> {
+    let mut channel = handle.clone().channel_open_session().await.ok()?;
+    channel.exec(true, command).await.ok()?;
+
+    let mut output = String::new();
+    while let Some(msg) = channel.wait().await {
+        match msg {
+            ChannelMsg::Data { data } => output.push_str(&String::from_utf8_lossy(&data)),
+            ChannelMsg::ExitStatus { .. } | ChannelMsg::Eof => break,
+            _ => {}
+        }
     }
+    Some(output)
 }
 
 impl SshSession {
@@ -40,35 +444,95 @@ impl SshSession {
             ..<_>::default()
         };
 
+        let forwards = ForwardRegistry::new();
+        let remote_targets = forwards.remote_targets();
+
+        let known_hosts_path = config
+            .known_hosts_path
+            .clone()
+            .unwrap_or_else(default_known_hosts_path);
+        let known_hosts = Arc::new(KnownHostsStore::load(known_hosts_path).await?);
+        let host_key_error: Arc<Mutex<Option<HostKeyError>>> = Arc::new(Mutex::new(None));
+
         let client_config = Arc::new(client_config);
-        let mut session = client::connect(
+        let connect_result = client::connect(
             client_config,
             (config.host.as_str(), config.port),
-            Client,
+            Client {
+                remote_targets: remote_targets.clone(),
+                host: config.host.clone(),
+                port: config.port,
+                known_hosts,
+                host_key_policy: config.host_key_policy,
+                host_key_error: host_key_error.clone(),
+            },
         )
-        .await
-        .context("Failed to connect to SSH server")?;
+        .await;
 
-        // Authenticate
-        let auth_result = if let Some(password) = config.password {
-            session
-                .authenticate_password(config.username.clone(), password)
-                .await
-                .context("Failed to authenticate with password")?
-        } else if let Some(key_data) = config.private_key {
-            let key = decode_secret_key(&key_data, None)
-                .context("Failed to decode private key")?;
-            let key_with_alg = PrivateKeyWithHashAlg::new(Arc::new(key), None);
-            session
-                .authenticate_publickey(config.username.clone(), key_with_alg)
-                .await
-                .context("Failed to authenticate with public key")?
+        let mut session = match connect_result {
+            Ok(session) => session,
+            Err(e) => {
+                if let Some(host_key_err) = host_key_error.lock().await.take() {
+                    return Err(anyhow::Error::new(host_key_err));
+                }
+                return Err(e).context("Failed to connect to SSH server");
+            }
+        };
+
+        // Authenticate, trying each configured method in turn against the
+        // same session so a server's "partial success" (e.g. a public key
+        // it accepts but that still needs a keyboard-interactive OTP)
+        // naturally chains into the next one instead of starting over.
+        let auth_methods = if config.auth_methods.is_empty() {
+            legacy_auth_methods(&config)
         } else {
-            return Err(anyhow::anyhow!("No authentication method provided"));
+            config.auth_methods.clone()
         };
 
-        if !auth_result.success() {
-            return Err(anyhow::anyhow!("Authentication failed"));
+        if auth_methods.is_empty() {
+            return Err(anyhow::anyhow!("No authentication method provided"));
+        }
+
+        let mut authenticated = false;
+        for method in &auth_methods {
+            let succeeded = match method {
+                AuthMethod::Agent => authenticate_with_agent(&mut session, &config).await?.success(),
+                AuthMethod::Password => {
+                    let Some(password) = config.password.clone() else {
+                        continue;
+                    };
+                    session
+                        .authenticate_password(config.username.clone(), password)
+                        .await
+                        .context("Failed to authenticate with password")?
+                        .success()
+                }
+                AuthMethod::PublicKey => {
+                    let Some(key_data) = config.private_key.clone() else {
+                        continue;
+                    };
+                    let key = decode_secret_key(&key_data, config.passphrase.as_deref())
+                        .map_err(|_| anyhow::Error::new(SshAuthError::BadPassphrase))?;
+                    let key_with_alg = PrivateKeyWithHashAlg::new(Arc::new(key), None);
+                    session
+                        .authenticate_publickey(config.username.clone(), key_with_alg)
+                        .await
+                        .context("Failed to authenticate with public key")?
+                        .success()
+                }
+                AuthMethod::KeyboardInteractive => {
+                    authenticate_keyboard_interactive(&mut session, &config).await?
+                }
+            };
+
+            if succeeded {
+                authenticated = true;
+                break;
+            }
+        }
+
+        if !authenticated {
+            return Err(anyhow::Error::new(SshAuthError::AuthRejected));
         }
 
         // Open a channel with PTY
@@ -77,12 +541,19 @@ impl SshSession {
             .await
             .context("Failed to open channel")?;
 
+        if config.use_agent && config.forward_agent {
+            channel
+                .agent_forward(false)
+                .await
+                .context("Failed to request agent forwarding")?;
+        }
+
         channel
             .request_pty(
                 false,
-                "xterm",
-                80,
-                24,
+                &config.term,
+                config.cols,
+                config.rows,
                 0,
                 0,
                 &[], //pty modes
@@ -95,12 +566,63 @@ impl SshSession {
             .await
             .context("Failed to request shell")?;
 
+        // Probe the remote end on a separate exec channel so it doesn't
+        // disturb the interactive PTY shell just opened above.
+        let family = detect_family(&session).await;
+
         Ok(Self {
             handle: session,
             channel,
+            forwards,
+            family,
         })
     }
 
+    /// The remote end's OS family, detected at connect time.
+    pub fn family(&self) -> SshFamily {
+        self.family
+    }
+
+    /// Open a TCP/UDP port forward tunneled through this session, returning
+    /// its id (used to close it later via `close_forward`).
+    pub async fn open_forward(&self, spec: ForwardSpec) -> Result<Uuid> {
+        self.forwards.open(self.handle.clone(), spec).await
+    }
+
+    /// Close a previously opened forward. Returns false if `id` isn't active.
+    pub async fn close_forward(&self, id: Uuid) -> bool {
+        self.forwards.close(id).await
+    }
+
+    /// Currently active forwards on this session.
+    pub async fn list_forwards(&self) -> Vec<(Uuid, ForwardSpec)> {
+        self.forwards.list().await
+    }
+
+    /// Subscribe to forward open/close/error events for this session.
+    pub fn subscribe_forwards(&self) -> tokio::sync::broadcast::Receiver<crate::forward::ForwardEvent> {
+        self.forwards.subscribe()
+    }
+
+    /// Open a fresh SFTP subsystem channel over this connection, for the
+    /// `fs_*` MCP tools. Each call gets its own channel rather than sharing
+    /// one, mirroring how `open_forward` opens a new channel per forward.
+    pub async fn open_sftp(&self) -> Result<russh_sftp::client::SftpSession> {
+        let channel = self
+            .handle
+            .clone()
+            .channel_open_session()
+            .await
+            .context("Failed to open SFTP channel")?;
+        channel
+            .request_subsystem(true, "sftp")
+            .await
+            .context("Failed to request SFTP subsystem")?;
+        russh_sftp::client::SftpSession::new(channel.into_stream())
+            .await
+            .context("Failed to start SFTP session")
+    }
+
     /// Send raw input to the SSH terminal (for user keystrokes)
     pub async fn send_input(&mut self, data: String) -> Result<()> {
         self.channel
@@ -112,7 +634,11 @@ impl SshSession {
 
     /// Execute a complete command (adds newline automatically)
     pub async fn execute_command(&mut self, command: String) -> Result<()> {
-        let cmd_with_newline = format!("{}\n", command);
+        let terminator = match self.family {
+            SshFamily::Windows => "\r\n",
+            SshFamily::Unix => "\n",
+        };
+        let cmd_with_newline = format!("{}{}", command, terminator);
         self.channel
             .data(Cursor::new(cmd_with_newline.into_bytes()))
             .await
@@ -152,7 +678,6 @@ impl SshSession {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub async fn close(self) -> Result<()> {
         self.channel
             .eof()
@@ -1,9 +1,15 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, Mutex, RwLock};
 use uuid::Uuid;
 
-use crate::mcp::{ApprovalChannel, McpSshService};
+use crate::gemini::{AgentBackend, GeminiTerminalSession, TerminalInit};
+use crate::mcp::{
+    ApprovalChannel, ApprovalPolicy, AuthChallengeChannel, McpSshService, RateLimiter,
+    CONNECTION_IDLE_TIMEOUT,
+};
+use crate::protocol::NegotiatedCapabilities;
 use crate::ssh::SshSession;
 
 /// Shared MCP services indexed by session ID.
@@ -19,17 +25,42 @@ pub struct Session {
     pub approval_channel: Arc<ApprovalChannel>,
     /// MCP service for this session
     pub mcp_service: Arc<McpSshService>,
+    /// Allow/deny lists and remembered decisions, shared with `mcp_service`
+    /// so the `/mcp/:session_id/policy` REST endpoints and a "remember this
+    /// decision" choice from the command WebSocket edit the same policy
+    /// tool calls read.
+    pub policy: Arc<RwLock<ApprovalPolicy>>,
     /// Channel to send SSH output to Gemini terminal
     pub ssh_to_gemini_tx: Option<Arc<Mutex<mpsc::UnboundedSender<String>>>>,
     /// Optional per-session Gemini API key (for web-based authentication)
     pub gemini_api_key: Option<String>,
+    /// The session's agent CLI terminal, shared across every WebSocket
+    /// connected to it. Lazily spawned by the first connection so idle
+    /// sessions don't pay for a process they never use.
+    pub gemini_terminal: Arc<Mutex<Option<Arc<GeminiTerminalSession>>>>,
+    /// Capabilities negotiated by the most recent WebSocket handshake on
+    /// this session, consulted by handlers to gate newer message variants.
+    /// `None` until a connection has completed the handshake.
+    pub negotiated_capabilities: Arc<RwLock<Option<NegotiatedCapabilities>>>,
+    /// When this session last had activity (WebSocket traffic or an MCP
+    /// `tools/call`), consulted by the session-TTL reaper to find sessions
+    /// an abandoned browser tab left behind. Shared with `mcp_service` so a
+    /// tool call counts too.
+    last_active: Arc<RwLock<Instant>>,
 }
 
 impl Session {
     pub fn new(gemini_api_key: Option<String>) -> Self {
         let id = Uuid::new_v4();
         let approval_channel = Arc::new(ApprovalChannel::new());
-        let mcp_service = Arc::new(McpSshService::new(id, approval_channel.clone()));
+        let policy = Arc::new(RwLock::new(ApprovalPolicy::default()));
+        let last_active = Arc::new(RwLock::new(Instant::now()));
+        let mcp_service = Arc::new(McpSshService::new(
+            id,
+            approval_channel.clone(),
+            policy.clone(),
+            last_active.clone(),
+        ));
 
         Self {
             id,
@@ -37,13 +68,54 @@ impl Session {
             ssh_output_buffer: Arc::new(RwLock::new(Vec::new())),
             approval_channel,
             mcp_service,
+            policy,
             ssh_to_gemini_tx: None,
             gemini_api_key,
+            gemini_terminal: Arc::new(Mutex::new(None)),
+            negotiated_capabilities: Arc::new(RwLock::new(None)),
+            last_active,
         }
     }
 
+    /// Get the session's agent CLI terminal, spawning it via `backend` on
+    /// first use. `init` (the first connection's terminal type/size/env) is
+    /// only consulted on that first spawn - later viewers attach to the
+    /// already-running process as-is. Returns the terminal plus whether
+    /// this call spawned it (so the caller only needs to do post-spawn
+    /// checks, like verifying the process didn't exit immediately, the
+    /// first time).
+    pub async fn get_or_spawn_gemini_terminal(
+        &self,
+        backend: &AgentBackend,
+        init: &TerminalInit,
+    ) -> anyhow::Result<(Arc<GeminiTerminalSession>, bool)> {
+        let mut slot = self.gemini_terminal.lock().await;
+        if let Some(terminal) = slot.as_ref() {
+            return Ok((terminal.clone(), false));
+        }
+
+        let terminal = Arc::new(
+            GeminiTerminalSession::spawn(backend, self.gemini_api_key.clone(), init).await?,
+        );
+        *slot = Some(terminal.clone());
+        Ok((terminal, true))
+    }
+
+    /// Record activity on this session, resetting the idle timer the
+    /// session-TTL reaper checks.
+    pub async fn touch(&self) {
+        *self.last_active.write().await = Instant::now();
+    }
+
+    /// How long since this session last had activity.
+    pub async fn idle_for(&self) -> Duration {
+        self.last_active.read().await.elapsed()
+    }
+
     /// Add SSH terminal output to the buffer
     pub async fn add_ssh_output(&self, output: String) {
+        self.touch().await;
+
         let mut buffer = self.ssh_output_buffer.write().await;
         buffer.push(output.clone());
 
@@ -74,6 +146,13 @@ impl Session {
         self.approval_channel.clone()
     }
 
+    /// Get the keyboard-interactive auth challenge channel for this
+    /// session's MCP service, so a WebSocket handler can subscribe/answer
+    /// without reaching through `get_mcp_service()` itself.
+    pub fn get_auth_challenge_channel(&self) -> Arc<AuthChallengeChannel> {
+        self.mcp_service.auth_challenge_channel.clone()
+    }
+
     /// Get the MCP service for this session.
     pub fn get_mcp_service(&self) -> Arc<McpSshService> {
         self.mcp_service.clone()
@@ -83,6 +162,27 @@ impl Session {
     pub async fn set_ssh_to_gemini_channel(&mut self, tx: mpsc::UnboundedSender<String>) {
         self.ssh_to_gemini_tx = Some(Arc::new(Mutex::new(tx)));
     }
+
+    /// Record the capability set negotiated by a WebSocket handshake,
+    /// replacing whatever a previous connection negotiated.
+    pub async fn set_negotiated_capabilities(&self, capabilities: NegotiatedCapabilities) {
+        *self.negotiated_capabilities.write().await = Some(capabilities);
+    }
+
+    /// Whether `capability` was negotiated by the most recent handshake.
+    /// `false` if no handshake has completed yet.
+    pub async fn has_capability(&self, capability: &str) -> bool {
+        match &*self.negotiated_capabilities.read().await {
+            Some(capabilities) => capabilities.has(capability),
+            None => false,
+        }
+    }
+
+    /// Remember a user's "always allow"/"always deny" choice for `command`
+    /// for the rest of the session's lifetime.
+    pub async fn remember_decision(&self, command: String, approved: bool) {
+        self.policy.write().await.remember(command, approved);
+    }
 }
 
 /// Global application state
@@ -91,6 +191,12 @@ pub struct AppState {
     pub sessions: Arc<RwLock<HashMap<Uuid, Session>>>,
     /// MCP services registry for tool access
     pub mcp_services: McpServices,
+    /// Per-session token-bucket limiter guarding `tools/call`.
+    pub mcp_rate_limiter: Arc<RateLimiter>,
+    /// Agent CLI backend new sessions spawn. `AppState::new` always sets
+    /// the Gemini default (`AgentBackend::gemini()`); no other backend is
+    /// read from configuration or the environment.
+    pub agent_backend: Arc<AgentBackend>,
 }
 
 impl AppState {
@@ -98,6 +204,8 @@ impl AppState {
         Self {
             sessions: Arc::new(RwLock::new(HashMap::new())),
             mcp_services: Arc::new(RwLock::new(HashMap::new())),
+            mcp_rate_limiter: Arc::new(RateLimiter::default()),
+            agent_backend: Arc::new(AgentBackend::default()),
         }
     }
 
@@ -127,6 +235,8 @@ impl AppState {
             mcp_services.remove(&id);
         }
 
+        self.mcp_rate_limiter.remove(&id);
+
         let mut sessions = self.sessions.write().await;
         sessions.remove(&id);
     }
@@ -136,3 +246,99 @@ impl AppState {
         self.mcp_services.clone()
     }
 }
+
+/// Session idle timeout if no activity reaps it, when `SESSION_TTL_SECONDS`
+/// isn't set.
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// How often the session-TTL reaper scans for idle sessions.
+const REAPER_SCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Session idle timeout read from `SESSION_TTL_SECONDS` (seconds), falling
+/// back to `DEFAULT_SESSION_TTL` if unset or unparseable.
+pub fn session_ttl_from_env() -> Duration {
+    std::env::var("SESSION_TTL_SECONDS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or(DEFAULT_SESSION_TTL)
+}
+
+/// Spawn a background task that periodically evicts sessions idle past
+/// `timeout`: closes the underlying SSH connection (if any) and removes the
+/// session from `state`. Without this, an abandoned browser tab leaks its
+/// `SshSession` and MCP service for the life of the process. Runs for the
+/// lifetime of the process.
+pub fn spawn_session_reaper(state: AppState, timeout: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(REAPER_SCAN_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let snapshot: Vec<(Uuid, Session)> = {
+                let sessions = state.sessions.read().await;
+                sessions.iter().map(|(id, s)| (*id, s.clone())).collect()
+            };
+
+            for (id, session) in snapshot {
+                let ssh_state = session.mcp_service.get_ssh_state();
+                let idle_connections = ssh_state.write().await.evict_idle(CONNECTION_IDLE_TIMEOUT).await;
+                for (name, entry) in idle_connections {
+                    tracing::info!(
+                        "Session {} reaping named SSH connection \"{}\" after {:?} idle",
+                        id,
+                        name,
+                        CONNECTION_IDLE_TIMEOUT
+                    );
+                    match Arc::try_unwrap(entry.session) {
+                        Ok(mutex) => {
+                            if let Err(e) = mutex.into_inner().close().await {
+                                tracing::warn!(
+                                    "Session {} connection \"{}\" didn't close cleanly: {}",
+                                    id,
+                                    name,
+                                    e
+                                );
+                            }
+                        }
+                        Err(_) => {
+                            tracing::warn!(
+                                "Session {} connection \"{}\" still in use elsewhere, dropping without a clean close",
+                                id,
+                                name
+                            );
+                        }
+                    }
+                }
+
+                if session.idle_for().await < timeout {
+                    continue;
+                }
+
+                tracing::info!("Reaping session {} after {:?} idle", id, timeout);
+
+                if let Some(ssh) = session.ssh_session.clone() {
+                    match Arc::try_unwrap(ssh) {
+                        Ok(mutex) => {
+                            if let Err(e) = mutex.into_inner().close().await {
+                                tracing::warn!(
+                                    "Session {} SSH connection didn't close cleanly: {}",
+                                    id,
+                                    e
+                                );
+                            }
+                        }
+                        Err(_) => {
+                            tracing::warn!(
+                                "Session {} SSH connection still in use elsewhere, dropping without a clean close",
+                                id
+                            );
+                        }
+                    }
+                }
+
+                state.remove_session(id).await;
+            }
+        }
+    })
+}
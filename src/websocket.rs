@@ -1,21 +1,28 @@
 use axum::{
     extract::{
         ws::{Message, WebSocket},
-        Path, State, WebSocketUpgrade,
+        Path, Query, State, WebSocketUpgrade,
     },
     response::Response,
     Json,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
-use std::io::{Read, Write};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::Mutex;
 use tokio_stream::wrappers::BroadcastStream;
 use uuid::Uuid;
 
-use crate::gemini::GeminiTerminal;
-use crate::mcp::ApprovalEvent;
+use crate::forward::{ForwardEvent, ForwardSpec};
+use crate::gemini::TerminalInit;
+use crate::lsp::{encode_frame, LspFramer, LspServer, RootMapping};
+use crate::mcp::{ApprovalEvent, AuthChallengeEvent};
+use crate::ssh::AuthPrompt;
+use crate::protocol::{self, HandshakeError};
+use crate::known_hosts::HostKeyPolicy;
 use crate::ssh::{SshConfig, SshSession};
 use crate::state::AppState;
 
@@ -40,6 +47,35 @@ pub struct SshConnectRequest {
     pub username: String,
     pub password: Option<String>,
     pub private_key: Option<String>,
+    /// Passphrase to decrypt `private_key`, if it's encrypted.
+    #[serde(default)]
+    pub passphrase: Option<String>,
+    /// Authenticate via the user's running ssh-agent instead of
+    /// `private_key`/`password`.
+    #[serde(default)]
+    pub use_agent: bool,
+    /// Agent socket path; defaults to `$SSH_AUTH_SOCK` when omitted.
+    #[serde(default)]
+    pub agent_socket: Option<String>,
+    /// Forward the agent connection to the remote (only meaningful with
+    /// `use_agent`).
+    #[serde(default)]
+    pub forward_agent: bool,
+    /// Terminal type to request the remote PTY with (e.g. `xterm-256color`).
+    /// Defaults to `xterm-256color` when omitted.
+    #[serde(default)]
+    pub term: Option<String>,
+    /// Initial PTY size; defaults to 80x24 when omitted.
+    #[serde(default)]
+    pub cols: Option<u32>,
+    #[serde(default)]
+    pub rows: Option<u32>,
+    /// How to handle the server's host key: `strict`, `accept_new`
+    /// (trust-on-first-use; default) or `accept_all`. Unlike the MCP
+    /// `ssh_connect` tool, a changed key can't be approved interactively
+    /// over this REST endpoint - it's simply reported as a connect error.
+    #[serde(default)]
+    pub host_key_policy: HostKeyPolicy,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,6 +88,16 @@ pub struct ConnectResponse {
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum TerminalMessage {
+    /// Sent by the browser terminal as its first message after the
+    /// handshake, so the spawned process gets the right `TERM` and size
+    /// instead of a hardcoded default from its first byte of output onward.
+    Init {
+        term: String,
+        cols: u32,
+        rows: u32,
+        #[serde(default)]
+        env: HashMap<String, String>,
+    },
     Input { data: String },
     Resize { width: u32, height: u32 },
     Output { data: String },
@@ -65,16 +111,88 @@ pub enum CommandMessage {
     CommandRequested {
         approval_id: String,
         command: String,
+        /// Named SSH connection the command targets, if any.
+        connection: Option<String>,
     },
     /// A command was approved (sent to frontend).
     CommandApproved { approval_id: String },
-    /// A command was rejected (sent to frontend).
-    CommandRejected { approval_id: String },
-    /// User decision on a command (received from frontend).
+    /// A command was rejected, optionally with a reason (sent to frontend).
+    CommandRejected {
+        approval_id: String,
+        reason: Option<String>,
+    },
+    /// The request was canceled rather than explicitly rejected (sent to frontend).
+    CommandCanceled { approval_id: String },
+    /// No decision arrived before the approval timeout elapsed (sent to frontend).
+    CommandTimedOut { approval_id: String },
+    /// User decision on a command (received from frontend). `remember`
+    /// requests that this exact command be auto-resolved the same way for
+    /// the rest of the session, via the session's `ApprovalPolicy`. `reason`
+    /// is only meaningful on a rejection, and is handed back to Gemini so it
+    /// can re-propose a safer command.
     CommandDecision {
         approval_id: String,
         approved: bool,
+        #[serde(default)]
+        remember: bool,
+        #[serde(default)]
+        reason: Option<String>,
     },
+    /// User explicitly canceled a pending approval (received from frontend).
+    CommandCancel { approval_id: String },
+    /// Open a TCP/UDP port forward tunneled through the session's SSH
+    /// connection (received from frontend).
+    ForwardOpen { spec: ForwardSpec },
+    /// A forward was opened (sent to frontend).
+    ForwardOpened { id: String, spec: ForwardSpec },
+    /// Close a previously opened forward by id (received from frontend).
+    ForwardClose { id: String },
+    /// A forward was closed (sent to frontend).
+    ForwardClosed { id: String },
+    /// A forward failed to open, or errored while running (sent to frontend).
+    ForwardErrored { id: String, message: String },
+    /// A keyboard-interactive auth prompt round is awaiting answers, e.g. an
+    /// MFA code (sent to frontend).
+    AuthChallengeRequested {
+        challenge_id: String,
+        name: String,
+        instructions: String,
+        prompts: Vec<AuthPrompt>,
+    },
+    /// User's answers to a keyboard-interactive auth prompt round, in the
+    /// same order as the prompts they answer (received from frontend).
+    AuthChallengeAnswer {
+        challenge_id: String,
+        answers: Vec<String>,
+    },
+}
+
+/// Query parameters for `/lsp/:session_id`, naming which language server to
+/// spawn and how to translate `file://` URIs between the browser's logical
+/// workspace root and the root the spawned server sees.
+#[derive(Debug, Deserialize)]
+pub struct LspConnectParams {
+    /// Language server binary to run (e.g. "rust-analyzer", "pyright-langserver").
+    pub command: String,
+    /// Workspace root as the browser/editor sees it in `file://` URIs.
+    pub client_root: String,
+    /// Workspace root as the spawned language server sees it, if different
+    /// (e.g. the SSH remote's filesystem or a container mount). Omit when
+    /// the server and client agree on paths.
+    #[serde(default)]
+    pub server_root: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LspMessage {
+    /// A JSON-RPC message from the browser client, relayed to the language
+    /// server's stdin.
+    Input { body: Value },
+    /// A JSON-RPC message from the language server's stdout, relayed to the
+    /// browser client.
+    Output { body: Value },
+    Error { message: String },
 }
 
 /// Create a new session
@@ -107,6 +225,20 @@ pub async fn ssh_connect_handler(
         username: request.username,
         password: request.password,
         private_key: request.private_key,
+        passphrase: request.passphrase,
+        use_agent: request.use_agent,
+        agent_socket: request.agent_socket,
+        forward_agent: request.forward_agent,
+        term: request.term.unwrap_or_else(|| "xterm-256color".to_string()),
+        cols: request.cols.unwrap_or(80),
+        rows: request.rows.unwrap_or(24),
+        host_key_policy: request.host_key_policy,
+        known_hosts_path: None,
+        // Keyboard-interactive auth needs a live channel back to the user
+        // for each prompt round, which this REST endpoint doesn't have -
+        // same limitation noted on `host_key_policy` above.
+        auth_methods: Vec::new(),
+        keyboard_interactive: None,
     };
 
     match SshSession::connect(ssh_config).await {
@@ -136,6 +268,37 @@ pub async fn ssh_connect_handler(
     }
 }
 
+/// Wait briefly for the browser terminal's `Init` message (term/cols/rows/env),
+/// sent right after the handshake so the PTY process spawned below gets the
+/// right `TERM` and size from its first byte of output onward. Returns the
+/// init to apply (defaults if none arrived in time or the client is too old
+/// to send one) plus, if the first message wasn't an `Init`, that message
+/// unconsumed so the caller can still process it instead of dropping it.
+async fn read_terminal_init(socket: &mut WebSocket) -> (TerminalInit, Option<Message>) {
+    let first = tokio::time::timeout(std::time::Duration::from_millis(500), socket.next()).await;
+    match first {
+        Ok(Some(Ok(Message::Text(text)))) => match serde_json::from_str::<TerminalMessage>(&text) {
+            Ok(TerminalMessage::Init {
+                term,
+                cols,
+                rows,
+                env,
+            }) => (
+                TerminalInit {
+                    term,
+                    cols: cols as u16,
+                    rows: rows as u16,
+                    env,
+                },
+                None,
+            ),
+            _ => (TerminalInit::default(), Some(Message::Text(text))),
+        },
+        Ok(Some(Ok(other))) => (TerminalInit::default(), Some(other)),
+        Ok(Some(Err(_))) | Ok(None) | Err(_) => (TerminalInit::default(), None),
+    }
+}
+
 /// Handle Gemini terminal WebSocket connection
 pub async fn gemini_terminal_ws_handler(
     ws: WebSocketUpgrade,
@@ -162,141 +325,135 @@ async fn gemini_terminal_connection(socket: WebSocket, session_id: String, state
         }
     };
 
-    // Get the per-session API key (may be None)
-    let api_key = session.gemini_api_key.clone();
+    let mut socket = match protocol::handshake(socket).await {
+        Ok((capabilities, socket)) => {
+            session.set_negotiated_capabilities(capabilities).await;
+            socket
+        }
+        Err(HandshakeError::VersionMismatch) => {
+            tracing::warn!("Gemini terminal client rejected: incompatible protocol version");
+            return;
+        }
+        Err(HandshakeError::Disconnected) => {
+            tracing::warn!("Gemini terminal client disconnected during handshake");
+            return;
+        }
+    };
+
+    // The browser terminal sends an `Init` right after the handshake with
+    // its terminal type/size/env, so a freshly spawned process gets those
+    // from its very first byte of output. Only matters for the first
+    // connection to spawn the process - give it a short window, and fall
+    // back to defaults (and replay whatever it actually sent) for clients
+    // that skip it.
+    let (term_init, leftover_msg) = read_terminal_init(&mut socket).await;
 
-    // Spawn Gemini CLI process with PTY, passing the session's API key
-    let gemini = match GeminiTerminal::spawn(api_key) {
-        Ok(g) => g,
+    // Attach to the session's shared agent CLI terminal, spawning it on the
+    // first connection so a dropped WebSocket (or a second viewer) doesn't
+    // lose or duplicate the running agent.
+    let (terminal, freshly_spawned) = match session
+        .get_or_spawn_gemini_terminal(&state.agent_backend, &term_init)
+        .await
+    {
+        Ok(t) => t,
         Err(e) => {
             tracing::error!("Failed to spawn Gemini CLI: {}", e);
             return;
         }
     };
 
-    // Keep gemini instance for resize operations and process monitoring
-    let gemini_arc = Arc::new(Mutex::new(gemini));
-
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
-    // Check if process is still running after spawn
-    tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
-    let gemini_check = gemini_arc.lock().await;
-    let is_running = gemini_check.is_running().await;
-    drop(gemini_check);
-
-    if !is_running {
-        tracing::error!(
-            "Gemini CLI process exited immediately after spawn - authentication required"
-        );
-        // Send error message to WebSocket
-        let error_msg = TerminalMessage::Output {
-            data: format!(
-                "\x1b[31m✗ Gemini CLI authentication required\x1b[0m\r\n\r\n\
-                Please set GEMINI_API_KEY environment variable:\r\n\
-                1. Get an API key from: \x1b[36mhttps://aistudio.google.com/apikey\x1b[0m\r\n\
-                2. Set the environment variable in docker-compose.yml:\r\n\
-                   \x1b[33m- GEMINI_API_KEY=your_api_key_here\x1b[0m\r\n\r\n\
-                Or authenticate with OAuth by running:\r\n\
-                   \x1b[33mdocker-compose exec gemini-co-cli gemini\x1b[0m\r\n\r\n\
-                MCP server available for Gemini CLI at:\r\n\
-                   \x1b[33mhttp://localhost:3000/mcp/{}\x1b[0m\r\n\r\n",
-                session_id
-            ),
-        };
-        let _ = ws_sender
-            .send(Message::Text(serde_json::to_string(&error_msg).unwrap()))
-            .await;
-        return; // Exit early since process is not running
+    if freshly_spawned {
+        // Give the process a moment to fail fast (e.g. missing auth) before
+        // committing to a normal session; later viewers skip this check.
+        tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        if !terminal.is_running().await {
+            tracing::error!(
+                "Gemini CLI process exited immediately after spawn - authentication required"
+            );
+            let error_msg = TerminalMessage::Output {
+                data: format!(
+                    "\x1b[31m✗ Gemini CLI authentication required\x1b[0m\r\n\r\n\
+                    Please set GEMINI_API_KEY environment variable:\r\n\
+                    1. Get an API key from: \x1b[36mhttps://aistudio.google.com/apikey\x1b[0m\r\n\
+                    2. Set the environment variable in docker-compose.yml:\r\n\
+                       \x1b[33m- GEMINI_API_KEY=your_api_key_here\x1b[0m\r\n\r\n\
+                    Or authenticate with OAuth by running:\r\n\
+                       \x1b[33mdocker-compose exec gemini-co-cli gemini\x1b[0m\r\n\r\n\
+                    MCP server available for Gemini CLI at:\r\n\
+                       \x1b[33mhttp://localhost:3000/mcp/{}\x1b[0m\r\n\r\n",
+                    session_id
+                ),
+            };
+            let _ = ws_sender
+                .send(Message::Text(serde_json::to_string(&error_msg).unwrap()))
+                .await;
+            return;
+        }
     }
 
-    let ws_sender = Arc::new(Mutex::new(ws_sender));
-
-    // Get PTY reader and writer
-    let gemini_for_io = gemini_arc.lock().await;
-    let mut reader = gemini_for_io.get_reader().await;
-    let mut writer = gemini_for_io.take_writer().await;
-    drop(gemini_for_io); // Release lock
-
-    // Task to read from PTY and send to WebSocket
-    // Note: Command detection is now handled via MCP tool calls, not text parsing
-    let ws_sender_clone = ws_sender.clone();
-    let mut output_task = tokio::task::spawn_blocking(move || {
-        let mut buffer = vec![0u8; 4096];
+    // Subscribe before replaying scrollback, so a chunk emitted in between
+    // is at worst a duplicate rather than silently dropped for this client.
+    let output_receiver = terminal.subscribe();
+    for chunk in terminal.scrollback().await {
+        let msg = TerminalMessage::Output { data: chunk };
+        if ws_sender
+            .send(Message::Text(serde_json::to_string(&msg).unwrap()))
+            .await
+            .is_err()
+        {
+            return;
+        }
+    }
 
-        tracing::info!("Gemini PTY output task started");
+    // Task to fan live output out to this WebSocket.
+    let mut output_task = tokio::spawn(async move {
+        let mut output_stream = BroadcastStream::new(output_receiver);
+        tracing::info!("Gemini PTY output relay started");
 
-        loop {
-            match reader.read(&mut buffer) {
-                Ok(n) if n > 0 => {
-                    let output = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    tracing::debug!("Gemini PTY output: {} bytes", n);
-
-                    // Send output to WebSocket
-                    let msg = TerminalMessage::Output { data: output };
-                    let json = serde_json::to_string(&msg).unwrap();
-
-                    // Use blocking channel to send to async task
-                    let rt = tokio::runtime::Handle::current();
-                    let sender = ws_sender_clone.clone();
-                    if rt
-                        .block_on(async {
-                            let mut s = sender.lock().await;
-                            s.send(Message::Text(json)).await
-                        })
-                        .is_err()
-                    {
-                        tracing::warn!("WebSocket closed, stopping Gemini PTY output");
-                        break;
-                    }
-                }
-                Ok(_) => {
-                    tracing::warn!("Gemini PTY reached EOF - process may have exited");
-                    break;
-                }
-                Err(e) => {
-                    tracing::error!("Error reading from Gemini PTY: {}", e);
-                    break;
-                }
+        while let Some(result) = output_stream.next().await {
+            let Ok(chunk) = result else {
+                continue; // lagged behind the broadcast buffer; skip ahead
+            };
+            let msg = TerminalMessage::Output { data: chunk };
+            let json = serde_json::to_string(&msg).unwrap();
+            if ws_sender.send(Message::Text(json)).await.is_err() {
+                tracing::warn!("WebSocket closed, stopping Gemini PTY output relay");
+                break;
             }
         }
-        tracing::info!("Gemini PTY output task ended");
+        tracing::info!("Gemini PTY output relay ended");
     });
 
-    // Task to handle WebSocket input and send to PTY
-    let gemini_for_resize = gemini_arc.clone();
-    let mut input_task = tokio::task::spawn_blocking(move || {
-        let rt = tokio::runtime::Handle::current();
-
+    // Task to serialize this client's input/resize requests onto the
+    // session's single shared writer.
+    let input_terminal = terminal.clone();
+    let touch_session = session.clone();
+    let mut input_task = tokio::spawn(async move {
         tracing::info!("Gemini PTY input task started");
 
-        loop {
-            // Receive from WebSocket in async context
-            let msg_opt = rt.block_on(async { ws_receiver.next().await });
+        // Replay whatever `read_terminal_init` consumed looking for `Init`
+        // but turned out not to be one, so it isn't silently dropped.
+        let mut incoming = futures::stream::iter(leftover_msg.map(Ok)).chain(ws_receiver);
 
-            match msg_opt {
-                Some(Ok(Message::Text(text))) => {
+        while let Some(Ok(msg)) = incoming.next().await {
+            touch_session.touch().await;
+            match msg {
+                Message::Text(text) => {
                     if let Ok(terminal_msg) = serde_json::from_str::<TerminalMessage>(&text) {
                         match terminal_msg {
                             TerminalMessage::Input { data } => {
-                                tracing::debug!("Gemini PTY input: {} bytes", data.len());
-                                if let Err(e) = writer.write_all(data.as_bytes()) {
-                                    tracing::error!("Error writing to Gemini PTY: {}", e);
-                                    break;
-                                }
-                                if let Err(e) = writer.flush() {
-                                    tracing::error!("Error flushing Gemini PTY: {}", e);
+                                if let Err(e) = input_terminal.write_input(&data).await {
+                                    tracing::error!("{}", e);
                                     break;
                                 }
                             }
                             TerminalMessage::Resize { width, height } => {
                                 tracing::info!("Gemini PTY resize: {}x{}", width, height);
-                                let gemini_resize = gemini_for_resize.clone();
-                                let resize_result = rt.block_on(async {
-                                    let gemini = gemini_resize.lock().await;
-                                    gemini.resize(width as u16, height as u16).await
-                                });
-                                if let Err(e) = resize_result {
+                                if let Err(e) =
+                                    input_terminal.resize(width as u16, height as u16).await
+                                {
                                     tracing::error!("Failed to resize Gemini PTY: {}", e);
                                 }
                             }
@@ -304,21 +461,18 @@ async fn gemini_terminal_connection(socket: WebSocket, session_id: String, state
                         }
                     }
                 }
-                Some(Ok(Message::Close(_))) | None => {
+                Message::Close(_) => {
                     tracing::info!("Gemini WebSocket closed");
                     break;
                 }
-                Some(Err(e)) => {
-                    tracing::error!("Gemini WebSocket error: {}", e);
-                    break;
-                }
                 _ => {}
             }
         }
         tracing::info!("Gemini PTY input task ended");
     });
 
-    // Wait for tasks to complete
+    // Wait for either task to finish - the terminal itself (and the other
+    // connected viewers, if any) keeps running either way.
     tokio::select! {
         _ = &mut output_task => {
             input_task.abort();
@@ -362,6 +516,21 @@ async fn ssh_terminal_connection(socket: WebSocket, session_id: String, state: A
         }
     };
 
+    let socket = match protocol::handshake(socket).await {
+        Ok((capabilities, socket)) => {
+            session.set_negotiated_capabilities(capabilities).await;
+            socket
+        }
+        Err(HandshakeError::VersionMismatch) => {
+            tracing::warn!("SSH terminal client rejected: incompatible protocol version");
+            return;
+        }
+        Err(HandshakeError::Disconnected) => {
+            tracing::warn!("SSH terminal client disconnected during handshake");
+            return;
+        }
+    };
+
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
     // Create channel for sending commands to SSH handler
@@ -440,8 +609,10 @@ async fn ssh_terminal_connection(socket: WebSocket, session_id: String, state: A
     });
 
     // Task to receive WebSocket messages and forward to SSH handler
+    let touch_session = session.clone();
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = ws_receiver.next().await {
+            touch_session.touch().await;
             if let Message::Text(text) = msg {
                 if let Ok(terminal_msg) = serde_json::from_str::<TerminalMessage>(&text) {
                     match terminal_msg {
@@ -502,6 +673,21 @@ async fn command_approval_connection(socket: WebSocket, session_id: String, stat
         }
     };
 
+    let socket = match protocol::handshake(socket).await {
+        Ok((capabilities, socket)) => {
+            session.set_negotiated_capabilities(capabilities).await;
+            socket
+        }
+        Err(HandshakeError::VersionMismatch) => {
+            tracing::warn!("Command approval client rejected: incompatible protocol version");
+            return;
+        }
+        Err(HandshakeError::Disconnected) => {
+            tracing::warn!("Command approval client disconnected during handshake");
+            return;
+        }
+    };
+
     let (mut sender, mut receiver) = socket.split();
 
     // Subscribe to approval events from the broadcast channel
@@ -509,63 +695,149 @@ async fn command_approval_connection(socket: WebSocket, session_id: String, stat
     let event_receiver = approval_channel.subscribe();
     let event_stream = BroadcastStream::new(event_receiver);
 
-    // Task to forward approval events to WebSocket
-    let mut event_task = tokio::spawn(async move {
-        tokio::pin!(event_stream);
-
-        while let Some(result) = event_stream.next().await {
-            if let Ok(event) = result {
-                let msg = match event {
-                    ApprovalEvent::CommandRequested {
-                        approval_id,
-                        command,
-                    } => CommandMessage::CommandRequested {
-                        approval_id,
-                        command,
-                    },
-                    ApprovalEvent::CommandApproved { approval_id } => {
-                        CommandMessage::CommandApproved { approval_id }
-                    }
-                    ApprovalEvent::CommandRejected { approval_id } => {
-                        CommandMessage::CommandRejected { approval_id }
-                    }
-                };
+    // Subscribe to this session's SSH forward events, if it has an SSH
+    // session yet (a forward can't be opened before one exists anyway). With
+    // no SSH session, use a stream that never resolves rather than one
+    // that's immediately (and repeatedly) exhausted, so it doesn't spin.
+    let forward_receiver = match &session.ssh_session {
+        Some(ssh) => Some(ssh.lock().await.subscribe_forwards()),
+        None => None,
+    };
+    // Subscribe to keyboard-interactive auth challenge prompts raised by
+    // `ssh_connect` calls on this session's MCP service.
+    let auth_challenge_channel = session.get_auth_challenge_channel();
+    let auth_challenge_stream = BroadcastStream::new(auth_challenge_channel.subscribe())
+        .filter_map(|r| async move { r.ok().and_then(auth_challenge_event_to_command_message) });
+    let approval_msg_stream = event_stream
+        .filter_map(|r| async move { r.ok().map(|recorded| approval_event_to_command_message(recorded.event)) });
+    let forward_msg_stream: std::pin::Pin<Box<dyn futures::Stream<Item = CommandMessage> + Send>> =
+        match forward_receiver {
+            Some(rx) => Box::pin(
+                BroadcastStream::new(rx)
+                    .filter_map(|r| async move { r.ok().map(forward_event_to_command_message) }),
+            ),
+            None => Box::pin(futures::stream::pending()),
+        };
+    let mut combined_stream = tokio_stream::StreamExt::merge(
+        tokio_stream::StreamExt::merge(approval_msg_stream, forward_msg_stream),
+        auth_challenge_stream,
+    );
 
-                let json = serde_json::to_string(&msg).unwrap();
-                if sender.send(Message::Text(json)).await.is_err() {
-                    tracing::warn!("WebSocket closed, stopping approval event forwarding");
-                    break;
-                }
+    // Task to forward approval and forward-status events to the WebSocket
+    let mut event_task = tokio::spawn(async move {
+        while let Some(msg) = combined_stream.next().await {
+            let json = serde_json::to_string(&msg).unwrap();
+            if sender.send(Message::Text(json)).await.is_err() {
+                tracing::warn!("WebSocket closed, stopping approval/forward event forwarding");
+                break;
             }
         }
     });
 
-    // Task to handle decisions from frontend
+    // Task to handle decisions and forward control messages from the frontend
     let approval_channel = session.get_approval_channel();
+    let auth_challenge_channel = session.get_auth_challenge_channel();
+    let ssh_session = session.ssh_session.clone();
+    let forward_session = session.clone();
+    let policy_session = session.clone();
+    let touch_session = session.clone();
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
+            touch_session.touch().await;
             if let Message::Text(text) = msg {
                 if let Ok(cmd_msg) = serde_json::from_str::<CommandMessage>(&text) {
-                    if let CommandMessage::CommandDecision {
-                        approval_id,
-                        approved,
-                    } = cmd_msg
-                    {
-                        if let Ok(id) = Uuid::parse_str(&approval_id) {
-                            let delivered = approval_channel.submit_decision(id, approved).await;
-                            if delivered {
-                                tracing::info!(
-                                    "Approval decision delivered: {} = {}",
-                                    approval_id,
-                                    approved
-                                );
-                            } else {
+                    match cmd_msg {
+                        CommandMessage::CommandDecision {
+                            approval_id,
+                            approved,
+                            remember,
+                            reason,
+                        } => {
+                            if let Ok(id) = Uuid::parse_str(&approval_id) {
+                                match approval_channel
+                                    .submit_decision(id, approved, reason)
+                                    .await
+                                {
+                                    Some(command) => {
+                                        tracing::info!(
+                                            "Approval decision delivered: {} = {}",
+                                            approval_id,
+                                            approved
+                                        );
+                                        if remember {
+                                            policy_session
+                                                .remember_decision(command, approved)
+                                                .await;
+                                        }
+                                    }
+                                    None => {
+                                        tracing::warn!(
+                                            "Approval decision not found (may have timed out or been canceled): {}",
+                                            approval_id
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                        CommandMessage::CommandCancel { approval_id } => {
+                            if let Ok(id) = Uuid::parse_str(&approval_id) {
+                                let delivered = approval_channel.submit_cancel(id).await;
+                                if delivered {
+                                    tracing::info!("Approval canceled: {}", approval_id);
+                                } else {
+                                    tracing::warn!(
+                                        "Approval cancel not found (may have already resolved): {}",
+                                        approval_id
+                                    );
+                                }
+                            }
+                        }
+                        CommandMessage::ForwardOpen { spec } => {
+                            if !forward_session.has_capability("port_forward").await {
                                 tracing::warn!(
-                                    "Approval decision not found (may have timed out): {}",
-                                    approval_id
+                                    "ForwardOpen received but port_forward wasn't negotiated"
                                 );
+                                continue;
+                            }
+                            let Some(ssh) = &ssh_session else {
+                                tracing::warn!("ForwardOpen received but no SSH session is connected");
+                                continue;
+                            };
+                            let ssh = ssh.lock().await;
+                            if let Err(e) = ssh.open_forward(spec).await {
+                                tracing::error!("Failed to open forward: {}", e);
                             }
                         }
+                        CommandMessage::ForwardClose { id } => {
+                            if !forward_session.has_capability("port_forward").await {
+                                continue;
+                            }
+                            let Some(ssh) = &ssh_session else {
+                                continue;
+                            };
+                            if let Ok(id) = Uuid::parse_str(&id) {
+                                let ssh = ssh.lock().await;
+                                ssh.close_forward(id).await;
+                            }
+                        }
+                        CommandMessage::AuthChallengeAnswer {
+                            challenge_id,
+                            answers,
+                        } => {
+                            if let Ok(id) = Uuid::parse_str(&challenge_id) {
+                                let delivered =
+                                    auth_challenge_channel.submit_answers(id, answers).await;
+                                if delivered {
+                                    tracing::info!("Auth challenge answered: {}", challenge_id);
+                                } else {
+                                    tracing::warn!(
+                                        "Auth challenge answer not found (may have timed out): {}",
+                                        challenge_id
+                                    );
+                                }
+                            }
+                        }
+                        _ => {}
                     }
                 }
             }
@@ -577,3 +849,244 @@ async fn command_approval_connection(socket: WebSocket, session_id: String, stat
         _ = &mut recv_task => event_task.abort(),
     };
 }
+
+fn approval_event_to_command_message(event: ApprovalEvent) -> CommandMessage {
+    match event {
+        ApprovalEvent::CommandRequested {
+            approval_id,
+            command,
+            connection,
+        } => CommandMessage::CommandRequested {
+            approval_id,
+            command,
+            connection,
+        },
+        ApprovalEvent::CommandApproved { approval_id } => {
+            CommandMessage::CommandApproved { approval_id }
+        }
+        ApprovalEvent::CommandRejected {
+            approval_id,
+            reason,
+        } => CommandMessage::CommandRejected {
+            approval_id,
+            reason,
+        },
+        ApprovalEvent::CommandCanceled { approval_id } => {
+            CommandMessage::CommandCanceled { approval_id }
+        }
+        ApprovalEvent::CommandTimedOut { approval_id } => {
+            CommandMessage::CommandTimedOut { approval_id }
+        }
+    }
+}
+
+/// Converts a broadcast auth challenge event into an outgoing
+/// `CommandMessage`, or `None` for events the frontend doesn't need to see -
+/// `PromptAnswered` is just this channel's own echo of the answer the
+/// frontend already sent, via `AuthChallengeAnswer`.
+fn auth_challenge_event_to_command_message(event: AuthChallengeEvent) -> Option<CommandMessage> {
+    match event {
+        AuthChallengeEvent::PromptRequested {
+            challenge_id,
+            name,
+            instructions,
+            prompts,
+        } => Some(CommandMessage::AuthChallengeRequested {
+            challenge_id,
+            name,
+            instructions,
+            prompts,
+        }),
+        AuthChallengeEvent::PromptAnswered { .. } => None,
+    }
+}
+
+fn forward_event_to_command_message(event: ForwardEvent) -> CommandMessage {
+    match event {
+        ForwardEvent::Opened { id, spec } => CommandMessage::ForwardOpened { id, spec },
+        ForwardEvent::Closed { id } => CommandMessage::ForwardClosed { id },
+        ForwardEvent::Errored { id, message } => CommandMessage::ForwardErrored { id, message },
+    }
+}
+
+/// Handle an LSP proxy WebSocket connection.
+///
+/// Spawns a new language server process for the connection and relays its
+/// `Content-Length`-framed JSON-RPC traffic to/from the browser, which
+/// instead sees one `LspMessage` per WebSocket text frame. `file://` URIs in
+/// `textDocument`/`rootUri` params are rewritten between the client's
+/// logical workspace root and the path the server actually sees, since the
+/// two can differ (e.g. the language server runs against an SSH remote or a
+/// container mount).
+pub async fn lsp_ws_handler(
+    ws: WebSocketUpgrade,
+    Path(session_id): Path<String>,
+    Query(params): Query<LspConnectParams>,
+    State(state): State<AppState>,
+) -> Response {
+    ws.on_upgrade(move |socket| lsp_connection(socket, session_id, params, state))
+}
+
+async fn lsp_connection(
+    socket: WebSocket,
+    session_id: String,
+    params: LspConnectParams,
+    state: AppState,
+) {
+    let session_uuid = match Uuid::parse_str(&session_id) {
+        Ok(id) => id,
+        Err(_) => {
+            tracing::error!("Invalid session ID: {}", session_id);
+            return;
+        }
+    };
+
+    let Some(session) = state.get_session(session_uuid).await else {
+        tracing::error!("Session not found: {}", session_id);
+        return;
+    };
+
+    let mut socket = match protocol::handshake(socket).await {
+        Ok((capabilities, socket)) => {
+            session.set_negotiated_capabilities(capabilities).await;
+            socket
+        }
+        Err(HandshakeError::VersionMismatch) => {
+            tracing::warn!("LSP client rejected: incompatible protocol version");
+            return;
+        }
+        Err(HandshakeError::Disconnected) => {
+            tracing::warn!("LSP client disconnected during handshake");
+            return;
+        }
+    };
+
+    if !session.has_capability("lsp").await {
+        let msg = LspMessage::Error {
+            message: "lsp capability was not negotiated".to_string(),
+        };
+        let _ = socket
+            .send(Message::Text(serde_json::to_string(&msg).unwrap()))
+            .await;
+        return;
+    }
+
+    let root_mapping = params.server_root.as_ref().map(|server_root| RootMapping {
+        client_root: params.client_root.clone(),
+        server_root: server_root.clone(),
+    });
+
+    let mut lsp = match LspServer::spawn(&params.command, &[]) {
+        Ok(l) => l,
+        Err(e) => {
+            tracing::error!("Failed to spawn language server {}: {}", params.command, e);
+            return;
+        }
+    };
+
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+
+    let mut stdout = match lsp.take_stdout() {
+        Some(s) => s,
+        None => {
+            tracing::error!("Language server stdout unavailable");
+            return;
+        }
+    };
+    let mut stdin = match lsp.take_stdin() {
+        Some(s) => s,
+        None => {
+            tracing::error!("Language server stdin unavailable");
+            return;
+        }
+    };
+
+    // Task to read Content-Length-framed JSON-RPC messages from the
+    // language server's stdout and forward each as an `LspMessage::Output`.
+    let output_mapping = root_mapping.clone();
+    let mut output_task = tokio::spawn(async move {
+        let mut framer = LspFramer::new();
+        let mut buf = [0u8; 4096];
+
+        loop {
+            match stdout.read(&mut buf).await {
+                Ok(0) => {
+                    tracing::info!("Language server stdout closed");
+                    break;
+                }
+                Ok(n) => {
+                    let messages = match framer.feed(&buf[..n]) {
+                        Ok(m) => m,
+                        Err(e) => {
+                            tracing::error!("Failed to decode LSP message: {}", e);
+                            break;
+                        }
+                    };
+
+                    for mut body in messages {
+                        if let Some(mapping) = &output_mapping {
+                            mapping.to_client(&mut body);
+                        }
+                        let msg = LspMessage::Output { body };
+                        let json = serde_json::to_string(&msg).unwrap();
+                        if ws_sender.send(Message::Text(json)).await.is_err() {
+                            tracing::warn!("WebSocket closed, stopping LSP output relay");
+                            return;
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::error!("Error reading language server stdout: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+
+    // Task to receive `LspMessage::Input` from the browser, reframe it with
+    // a Content-Length header, and write it to the language server's stdin.
+    let touch_session = session.clone();
+    let mut input_task = tokio::spawn(async move {
+        while let Some(Ok(msg)) = ws_receiver.next().await {
+            touch_session.touch().await;
+            match msg {
+                Message::Text(text) => {
+                    let Ok(LspMessage::Input { mut body }) =
+                        serde_json::from_str::<LspMessage>(&text)
+                    else {
+                        continue;
+                    };
+
+                    if let Some(mapping) = &root_mapping {
+                        mapping.to_server(&mut body);
+                    }
+
+                    let frame = match encode_frame(&body) {
+                        Ok(f) => f,
+                        Err(e) => {
+                            tracing::error!("Failed to encode LSP message: {}", e);
+                            continue;
+                        }
+                    };
+
+                    if stdin.write_all(&frame).await.is_err() || stdin.flush().await.is_err() {
+                        tracing::error!("Error writing to language server stdin");
+                        break;
+                    }
+                }
+                Message::Close(_) => {
+                    tracing::info!("LSP WebSocket closed");
+                    break;
+                }
+                _ => {}
+            }
+        }
+    });
+
+    tokio::select! {
+        _ = &mut output_task => input_task.abort(),
+        _ = &mut input_task => output_task.abort(),
+    };
+
+    lsp.kill().await;
+}